@@ -0,0 +1,25 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Write the C header for the `ffi` module to `OUT_DIR`
+///
+/// The committed copy in `include/` is refreshed separately with the cbindgen
+/// CLI, so building never rewrites a tracked file.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("invalid cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate the C header")
+        .write_to_file(format!("{}/dutchdutch_ascend.h", out_dir));
+}
@@ -4,7 +4,7 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use dutchdutch_ascend::{
-    AscendClient, Discovery, Room,
+    AscendClient, Discovery, GainDb, Room,
 };
 use tokio::sync::broadcast;
 use ratatui::{
@@ -28,7 +28,7 @@ struct App {
     discovery: Discovery,
     selected_room_index: usize,
     connected_client: Option<AscendClient>,
-    selected_room_id: Option<uuid::Uuid>,
+    selected_room: Option<Room>,
     status_message: String,
     update_receiver: Option<broadcast::Receiver<uuid::Uuid>>,
     json_cursor: usize,
@@ -45,7 +45,7 @@ impl App {
             discovery,
             selected_room_index: 0,
             connected_client: None,
-            selected_room_id: None,
+            selected_room: None,
             status_message: "Discovering rooms...".to_string(),
             update_receiver: Some(update_receiver),
             json_cursor: 0,
@@ -81,8 +81,8 @@ impl App {
         let room = &rooms[self.selected_room_index];
         self.status_message = format!("Selecting {}...", room.name());
 
-        // Store just the ID, we'll get the room from discovery each time
-        self.selected_room_id = Some(room.id());
+        // Rooms compare by ID, so the selection can be looked up in discovery each time
+        self.selected_room = Some(room.clone());
         self.state = AppState::RoomControl;
         self.status_message = "Connected! Use +/- for volume, m for mute, q to quit, Esc to go back".to_string();
 
@@ -90,14 +90,14 @@ impl App {
     }
 
     fn get_current_room(&self) -> Option<Room> {
-        let room_id = self.selected_room_id?;
-        self.discovery.rooms().into_iter().find(|r| r.id() == room_id)
+        let selected = self.selected_room.as_ref()?;
+        self.discovery.rooms().into_iter().find(|r| r == selected)
     }
 
-    async fn adjust_volume(&mut self, delta: f64) -> Result<(), Box<dyn std::error::Error>> {
+    async fn adjust_volume(&mut self, delta: GainDb) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(room) = self.get_current_room() {
-            let current_gain = room.gain().global;
-            let new_gain = (current_gain + delta).clamp(-80.0, 10.0);
+            let gain = room.gain();
+            let new_gain = (gain.global + delta).clamp_to(&gain.limits);
 
             if let Err(e) = room.set_gain(new_gain).await {
                 self.status_message = format!("Failed to set gain: {}", e);
@@ -209,7 +209,7 @@ impl App {
             match receiver.try_recv() {
                 Ok(updated_room_id) => {
                     // Check if this is the room we're currently viewing
-                    if self.selected_room_id == Some(updated_room_id) {
+                    if self.selected_room.as_ref().is_some_and(|room| room.id() == updated_room_id) {
                         // Room will automatically show updated state on next render
                         self.status_message = "State updated from network".to_string();
                     }
@@ -228,7 +228,7 @@ impl App {
     fn go_back(&mut self) {
         self.state = AppState::Discovery;
         self.connected_client = None;
-        self.selected_room_id = None;
+        self.selected_room = None;
         self.json_cursor = 0;
         self.json_scroll = 0;
         self.status_message = format!("Discovered {} room(s). Press Enter to connect.", self.discovery.room_count());
@@ -418,7 +418,7 @@ fn render_room_control(f: &mut Frame, app: &App, area: Rect) {
         // Input modes
         if !state.input_modes.is_empty() {
             lines.push(Line::from(Span::styled("Inputs:", Style::default().fg(Color::Yellow))));
-            for input in &state.input_modes {
+            for input in state.input_modes.iter() {
                 let is_active = state.selected_input.as_ref() == Some(input);
                 let prefix = if is_active { "  ▶ " } else { "    " };
                 lines.push(Line::from(vec![
@@ -439,7 +439,7 @@ fn render_room_control(f: &mut Frame, app: &App, area: Rect) {
         // XLR modes
         if !state.xlr_input_modes.is_empty() {
             lines.push(Line::from(Span::styled("XLR Modes:", Style::default().fg(Color::Yellow))));
-            for xlr_mode in &state.xlr_input_modes {
+            for xlr_mode in state.xlr_input_modes.iter() {
                 let is_active = state.selected_xlr.as_ref() == Some(xlr_mode);
                 let prefix = if is_active { "  ▶ " } else { "    " };
                 lines.push(Line::from(vec![
@@ -458,38 +458,27 @@ fn render_room_control(f: &mut Frame, app: &App, area: Rect) {
         }
 
         // Tone settings (from selected voicing)
-        if let Some(selected_id) = &state.selected_voicing_profile {
-            if let Some(voicing_profile) = state.voicing.get(selected_id) {
-                lines.push(Line::from(Span::styled("Tone:", Style::default().fg(Color::Yellow))));
-                lines.push(Line::from(vec![
-                    Span::raw("  Sub:    "),
-                    Span::styled(
-                        format!("{:.1} dB", voicing_profile.sub),
-                        Style::default().fg(Color::Cyan),
-                    ),
-                ]));
-                lines.push(Line::from(vec![
-                    Span::raw("  Bass:   "),
-                    Span::styled(
-                        format!("{:.1} dB", voicing_profile.bass),
-                        Style::default().fg(Color::Cyan),
-                    ),
-                ]));
-                lines.push(Line::from(vec![
-                    Span::raw("  Treble: "),
-                    Span::styled(
-                        format!("{:.1} dB", voicing_profile.treble),
-                        Style::default().fg(Color::Cyan),
-                    ),
-                ]));
-                lines.push(Line::from(""));
-            }
+        if let Some(tone) = state.current_tone() {
+            lines.push(Line::from(Span::styled("Tone:", Style::default().fg(Color::Yellow))));
+            lines.push(Line::from(vec![
+                Span::raw("  Sub:    "),
+                Span::styled(format!("{:.1} dB", tone.sub), Style::default().fg(Color::Cyan)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw("  Bass:   "),
+                Span::styled(format!("{:.1} dB", tone.mid), Style::default().fg(Color::Cyan)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw("  Treble: "),
+                Span::styled(format!("{:.1} dB", tone.treble), Style::default().fg(Color::Cyan)),
+            ]));
+            lines.push(Line::from(""));
         }
 
         // Voicing profiles
         if !state.voicing.is_empty() {
             lines.push(Line::from(Span::styled("Voicings:", Style::default().fg(Color::Yellow))));
-            for (voicing_id, voicing_prof) in &state.voicing {
+            for (voicing_id, voicing_prof) in state.voicing.iter() {
                 let is_active = state.selected_voicing_profile.as_ref() == Some(voicing_id);
                 let prefix = if is_active { "  ▶ " } else { "    " };
                 lines.push(Line::from(vec![
@@ -510,7 +499,7 @@ fn render_room_control(f: &mut Frame, app: &App, area: Rect) {
         if !state.presets.is_empty() {
             lines.push(Line::from(""));
             lines.push(Line::from(Span::styled("Presets:", Style::default().fg(Color::Yellow))));
-            for (preset_id, preset) in &state.presets {
+            for (preset_id, preset) in state.presets.iter() {
                 let is_active = state.last_selected_preset.as_ref() == Some(preset_id);
                 let prefix = if is_active { "  ▶ " } else { "    " };
                 lines.push(Line::from(vec![
@@ -684,10 +673,10 @@ async fn run_app(
                             KeyCode::Char('q') => return Ok(()),
                             KeyCode::Esc => app.go_back(),
                             KeyCode::Char('+') | KeyCode::Char('=') => {
-                                app.adjust_volume(1.0).await?;
+                                app.adjust_volume(GainDb::new(1.0)).await?;
                             }
                             KeyCode::Char('-') | KeyCode::Char('_') => {
-                                app.adjust_volume(-1.0).await?;
+                                app.adjust_volume(GainDb::new(-1.0)).await?;
                             }
                             KeyCode::Char('m') => {
                                 app.toggle_mute().await?;
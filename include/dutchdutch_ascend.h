@@ -0,0 +1,148 @@
+#ifndef DUTCHDUTCH_ASCEND_H
+#define DUTCHDUTCH_ASCEND_H
+
+/* Generated by cbindgen from src/ffi.rs with `cbindgen --config cbindgen.toml --output include/dutchdutch_ascend.h`. Do not edit. */
+
+#include <stdbool.h>
+#include <stdint.h>
+
+// Result of an FFI call
+typedef enum AscendStatus {
+  ASCEND_STATUS_OK = 0,
+  // A pointer was null or a string was not valid UTF-8
+  ASCEND_STATUS_INVALID_ARGUMENT = 1,
+  // The connection could not be established or was lost
+  ASCEND_STATUS_CONNECTION_FAILED = 2,
+  // The speaker did not answer in time
+  ASCEND_STATUS_TIMEOUT = 3,
+  // The speaker rejected the request
+  ASCEND_STATUS_API_ERROR = 4,
+  // The requested room does not exist, or several match
+  ASCEND_STATUS_ROOM_NOT_FOUND = 5,
+  // The speaker's firmware does not support the request
+  ASCEND_STATUS_UNSUPPORTED = 6,
+  // Any other error
+  ASCEND_STATUS_OTHER = 7,
+  // A blocking function was called from inside a state callback
+  ASCEND_STATUS_IN_CALLBACK = 8,
+} AscendStatus;
+
+// Opaque handle to a connected client
+typedef struct AscendClientHandle AscendClientHandle;
+
+// Opaque handle to a room
+typedef struct AscendRoomHandle AscendRoomHandle;
+
+// Room state passed to an [`AscendStateCallback`]
+//
+// The strings are only valid for the duration of the callback.
+typedef struct AscendRoomState {
+  // Global gain in dB
+  double gain;
+  bool mute;
+  bool standby;
+  // Selected input, or null if none is reported
+  const char *input;
+  // The complete room document as JSON
+  const char *json;
+} AscendRoomState;
+
+// Called with the new room state after every change
+//
+// Invoked on a library-owned runtime thread, never concurrently for the same
+// room. The callback must not block, and blocking library calls made from it
+// fail with `InCallback`.
+typedef void (*AscendStateCallback)(const struct AscendRoomState *state, void *user_data);
+
+// Describe the last error on the calling thread
+//
+// Returns null if no call on this thread has failed yet. The string stays
+// valid until the next failing call on the same thread.
+const char *ascend_last_error(void);
+
+// Connect to a speaker
+//
+// `address` accepts `host`, `host:port`, or `ws://host:port`. On success,
+// `*out` receives a handle to release with `ascend_client_free`.
+//
+// # Safety
+//
+// `address` must be a NUL-terminated string and `out` a valid pointer.
+enum AscendStatus ascend_client_connect(const char *address, struct AscendClientHandle **out);
+
+// Disconnect and release a client
+//
+// Room handles obtained from the client stay valid and their requests keep
+// working, since each holds its own reference to the speaker connection; it
+// is closed once the client and all of its rooms are freed. Changes reported
+// by the speaker are no longer applied to those rooms, so their state, and
+// their state callbacks, only follow their own setters.
+//
+// # Safety
+//
+// `client` must be null or a handle from `ascend_client_connect` that has not
+// been freed.
+void ascend_client_free(struct AscendClientHandle *client);
+
+// Get a room by name, or the only room if `name` is null
+//
+// On success, `*out` receives a handle to release with `ascend_room_free`.
+//
+// # Safety
+//
+// `client` must be a valid client handle, `name` null or a NUL-terminated
+// string, and `out` a valid pointer.
+enum AscendStatus ascend_client_room(const struct AscendClientHandle *client,
+                                     const char *name,
+                                     struct AscendRoomHandle **out);
+
+// Release a room handle, unregistering its state callback
+//
+// # Safety
+//
+// `room` must be null or a handle from `ascend_client_room` that has not been
+// freed.
+void ascend_room_free(struct AscendRoomHandle *room);
+
+// Get the room's global gain in dB
+//
+// # Safety
+//
+// `room` must be a valid room handle and `out` a valid pointer.
+enum AscendStatus ascend_room_gain(const struct AscendRoomHandle *room, double *out);
+
+// Set the room's global gain in dB
+//
+// # Safety
+//
+// `room` must be a valid room handle.
+enum AscendStatus ascend_room_set_gain(const struct AscendRoomHandle *room, double gain);
+
+// Get the room's global mute state
+//
+// # Safety
+//
+// `room` must be a valid room handle and `out` a valid pointer.
+enum AscendStatus ascend_room_mute(const struct AscendRoomHandle *room, bool *out);
+
+// Set the room's global mute state
+//
+// # Safety
+//
+// `room` must be a valid room handle.
+enum AscendStatus ascend_room_set_mute(const struct AscendRoomHandle *room, bool mute);
+
+// Register a callback invoked after every change to the room's state
+//
+// Replaces any previously registered callback. Pass a null callback to
+// unregister.
+//
+// # Safety
+//
+// `room` must be a valid room handle. `user_data` is passed back unchanged
+// and must stay valid until the callback is replaced or the room is freed.
+enum AscendStatus ascend_room_set_state_callback(struct AscendRoomHandle *room,
+                                                 AscendStateCallback callback,
+                                                 void *user_data);
+
+#endif  /* DUTCHDUTCH_ASCEND_H */
@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::room::Room;
+use crate::types::{GainDb, MuteState};
+
+/// A change queued in a `RoomBatch`
+#[derive(Debug, Clone)]
+pub(crate) enum BatchOp {
+    Gain(GainDb),
+    Mute(MuteState),
+    Input(String),
+    Voicing(String),
+    Preset(String),
+    Standby(bool),
+}
+
+/// Several room changes sent back-to-back
+///
+/// Created by `Room::batch`. The requests are written in the order they were
+/// queued, and all of them are written before any response is awaited, so the
+/// batch costs roughly one round trip instead of one per change. Each change
+/// is checked like the corresponding `Room` setter, updates the local state
+/// once acknowledged, and is recorded for undo. A gain rise beyond the slew
+/// limit fails rather than ramping, even in `SlewMode::Smooth`.
+///
+/// With `ConnectOptions::serialize_room_commands` enabled, the room's queue is
+/// held for the whole batch rather than for each change.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dutchdutch_ascend::AscendClient;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+/// let room = client.room().await?;
+/// let results = room.batch()
+///     .input("XLR")
+///     .voicing("Neutral")
+///     .gain(-25.0)
+///     .send()
+///     .await;
+/// for result in results {
+///     result?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "a batch does nothing until `send` is awaited"]
+pub struct RoomBatch {
+    room: Room,
+    ops: Vec<BatchOp>,
+}
+
+impl RoomBatch {
+    pub(crate) fn new(room: Room) -> Self {
+        Self {
+            room,
+            ops: Vec::new(),
+        }
+    }
+
+    /// Queue a global gain change in dB
+    pub fn gain(mut self, gain: impl Into<GainDb>) -> Self {
+        self.ops.push(BatchOp::Gain(gain.into()));
+        self
+    }
+
+    /// Queue a global mute change
+    pub fn mute(mut self, mute: MuteState) -> Self {
+        self.ops.push(BatchOp::Mute(mute));
+        self
+    }
+
+    /// Queue an input selection
+    pub fn input(mut self, input: impl Into<String>) -> Self {
+        self.ops.push(BatchOp::Input(input.into()));
+        self
+    }
+
+    /// Queue a voicing profile selection
+    pub fn voicing(mut self, profile: impl Into<String>) -> Self {
+        self.ops.push(BatchOp::Voicing(profile.into()));
+        self
+    }
+
+    /// Queue a preset selection
+    pub fn preset(mut self, preset_id: impl Into<String>) -> Self {
+        self.ops.push(BatchOp::Preset(preset_id.into()));
+        self
+    }
+
+    /// Queue a standby change
+    pub fn standby(mut self, standby: bool) -> Self {
+        self.ops.push(BatchOp::Standby(standby));
+        self
+    }
+
+    /// Get the number of queued changes
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Check if no changes are queued
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Send all queued changes in order and wait for every response
+    ///
+    /// Returns the result of each change, in the order they were queued.
+    /// Every change is attempted, whether or not the others fail.
+    pub async fn send(self) -> Vec<Result<()>> {
+        self.room.send_batch(self.ops).await
+    }
+}
@@ -0,0 +1,276 @@
+//! Command-line control of Ascend speakers (requires the `cli` feature)
+//!
+//! ```text
+//! ascendctl discover
+//! ascendctl --host 192.168.1.100 status
+//! ascendctl --host 192.168.1.100 --room Studio volume set -25
+//! ascendctl --host 192.168.1.100 watch --json
+//! ```
+//!
+//! The speaker address can also be given in the `ASCEND_HOST` environment
+//! variable.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use dutchdutch_ascend::{AscendClient, AscendError, ConnectionStatus, Discovery, GainDb, Room, RoomId};
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "ascendctl", version, about = "Control Dutch and Dutch Ascend speakers")]
+struct Cli {
+    /// Speaker address (`host`, `host:port`, or `ws://host:port`)
+    #[arg(long, env = "ASCEND_HOST", global = true)]
+    host: Option<String>,
+
+    /// Room name or ID (required when the speaker serves several rooms)
+    #[arg(long, env = "ASCEND_ROOM", global = true)]
+    room: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Find speakers on the local network via the discovery service
+    Discover {
+        /// Seconds to wait for speakers to report
+        #[arg(long, default_value_t = 3)]
+        wait: u64,
+    },
+    /// Show the state of the selected room, or of every room
+    Status,
+    /// Show or change the volume
+    Volume {
+        #[command(subcommand)]
+        action: Option<VolumeAction>,
+    },
+    /// Show or change the mute state
+    Mute { state: Option<Switch> },
+    /// Show the available inputs, or select one
+    Input { name: Option<String> },
+    /// List or select presets
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
+    },
+    /// Show the available voicing profiles, or select one
+    Voicing { name: Option<String> },
+    /// Show or change standby
+    Standby { state: Option<Switch> },
+    /// Print room state changes until interrupted
+    Watch {
+        /// Print each change as the room's JSON document on one line
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum VolumeAction {
+    /// Set the volume in dB
+    Set {
+        #[arg(allow_negative_numbers = true)]
+        gain: GainDb,
+    },
+    /// Raise the volume
+    Up {
+        /// Step in dB (defaults to the room's gain step)
+        #[arg(long)]
+        step: Option<GainDb>,
+    },
+    /// Lower the volume
+    Down {
+        /// Step in dB (defaults to the room's gain step)
+        #[arg(long)]
+        step: Option<GainDb>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PresetAction {
+    /// List presets
+    List,
+    /// Select and apply a preset by ID
+    Select { id: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Switch {
+    On,
+    Off,
+    Toggle,
+}
+
+impl Switch {
+    fn apply(self, current: bool) -> bool {
+        match self {
+            Self::On => true,
+            Self::Off => false,
+            Self::Toggle => !current,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ascendctl: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> dutchdutch_ascend::Result<()> {
+    if let Command::Discover { wait } = cli.command {
+        return discover(Duration::from_secs(wait)).await;
+    }
+
+    let host = cli.host.ok_or_else(|| {
+        AscendError::InvalidArgument("No speaker address; pass --host or set ASCEND_HOST".to_string())
+    })?;
+    let client = AscendClient::connect_url(&host).await?;
+
+    if let (Command::Status, None) = (&cli.command, &cli.room) {
+        for room in client.rooms().await? {
+            print_status(&room);
+        }
+        return Ok(());
+    }
+
+    let room = match &cli.room {
+        Some(room) => match room.parse::<RoomId>() {
+            Ok(room_id) => client.room_by_id(room_id).await?,
+            Err(_) => client.room_by_name(room).await?,
+        },
+        None => client.room().await?,
+    };
+
+    match cli.command {
+        Command::Discover { .. } => unreachable!("handled before connecting"),
+        Command::Status => print_status(&room),
+        Command::Volume { action: None } => println!("{:.1} dB", room.gain().global),
+        Command::Volume { action: Some(action) } => {
+            let gain = room.gain();
+            let target = match action {
+                VolumeAction::Set { gain } => gain,
+                VolumeAction::Up { step } => gain.global + step.unwrap_or(GainDb::new(gain.step())),
+                VolumeAction::Down { step } => gain.global - step.unwrap_or(GainDb::new(gain.step())),
+            };
+            let target = target.clamp_to(&gain.limits);
+            room.set_gain(target).await?;
+            println!("{:.1} dB", target);
+        }
+        Command::Mute { state: None } => println!("{}", on_off(room.mute().global)),
+        Command::Mute { state: Some(state) } => {
+            let mute = state.apply(room.mute().global);
+            room.set_mute(mute).await?;
+            println!("{}", on_off(mute));
+        }
+        Command::Input { name: None } => {
+            let selected = room.selected_input();
+            for input in room.input_modes() {
+                print_choice(&input, selected.as_deref() == Some(input.as_str()));
+            }
+        }
+        Command::Input { name: Some(name) } => room.set_input(name).await?,
+        Command::Preset { action: PresetAction::List } => {
+            let selected = room.last_selected_preset();
+            for (id, preset) in room.presets() {
+                let label = format!("{}\t{}", id, preset.name);
+                print_choice(&label, selected.as_deref() == Some(id.as_str()));
+            }
+        }
+        Command::Preset { action: PresetAction::Select { id } } => room.select_preset(id).await?,
+        Command::Voicing { name: None } => {
+            let selected = room.selected_voicing_profile();
+            for name in room.voicing_profiles().into_keys() {
+                print_choice(&name, selected.as_deref() == Some(name.as_str()));
+            }
+        }
+        Command::Voicing { name: Some(name) } => room.select_voicing(name).await?,
+        Command::Standby { state: None } => println!("{}", on_off(room.sleep())),
+        Command::Standby { state: Some(state) } => {
+            let standby = state.apply(room.sleep());
+            room.set_standby(standby).await?;
+            println!("{}", on_off(standby));
+        }
+        Command::Watch { json } => watch(&client, &room, json).await?,
+    }
+
+    Ok(())
+}
+
+/// Run discovery for a while and list the rooms found
+async fn discover(wait: Duration) -> dutchdutch_ascend::Result<()> {
+    let mut discovery = Discovery::new();
+    discovery.start().await?;
+    tokio::time::sleep(wait).await;
+    discovery.stop().await;
+
+    for room in discovery.rooms() {
+        println!("{}\t{}\t{}", room.speaker_address(), room.id(), room.name());
+    }
+    Ok(())
+}
+
+/// Print room changes until the connection drops
+async fn watch(client: &AscendClient, room: &Room, json: bool) -> dutchdutch_ascend::Result<()> {
+    let mut states = room.watch();
+    let mut status = client.connection_status();
+
+    loop {
+        tokio::select! {
+            changed = states.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+                if json {
+                    println!("{}", states.borrow_and_update().raw_json);
+                } else {
+                    let state = states.borrow_and_update().clone();
+                    println!(
+                        "{}: {:.1} dB, mute {}, standby {}, input {}",
+                        state.name,
+                        state.gain.global,
+                        on_off(state.mute.global),
+                        on_off(state.sleep),
+                        state.selected_input.as_deref().unwrap_or("-"),
+                    );
+                }
+            }
+            changed = status.changed() => {
+                if changed.is_err() || *status.borrow_and_update() == ConnectionStatus::Disconnected {
+                    return Err(AscendError::ConnectionClosed);
+                }
+            }
+        }
+    }
+}
+
+fn print_status(room: &Room) {
+    let state = room.state_snapshot();
+    println!("{} ({})", state.name, state.id);
+    println!("  Volume:  {:.1} dB", state.gain.global);
+    println!("  Mute:    {}", on_off(state.mute.global));
+    println!("  Standby: {}", on_off(state.sleep));
+    println!("  Input:   {}", state.selected_input.as_deref().unwrap_or("-"));
+    println!("  Voicing: {}", state.selected_voicing_profile.as_deref().unwrap_or("-"));
+    println!("  Preset:  {}", state.last_selected_preset.as_deref().unwrap_or("-"));
+}
+
+/// Print one entry of a list, marking the selected one
+fn print_choice(label: &str, selected: bool) {
+    println!("{} {}", if selected { "*" } else { " " }, label);
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
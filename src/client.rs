@@ -1,8 +1,127 @@
+use crate::connection::{ConnectOptions, ConnectionStatus};
+use crate::device::DeviceHandle;
 use crate::error::{AscendError, Result};
+use crate::protocol::{Method, Request, Response, TargetType};
 use crate::room::Room;
 use crate::speaker_connection::SpeakerConnection;
-use crate::subscription::StateReceiver;
-use std::sync::Arc;
+use crate::subscription::{RoomReceiver, StateReceiver, StateUpdate};
+use crate::transport::Transport;
+use crate::types::{Capabilities, Device, DeviceId, Entity, RoomId, SpeakerAddress, SystemInfo, TargetsResponse};
+use futures_util::future::join_all;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// How long cached rooms are served without rereading the network state
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Rooms handed out by the client, kept current by the state subscription
+struct RoomCache {
+    rooms: BTreeMap<RoomId, Room>,
+    /// When the network state was last read; `None` forces a reread
+    fetched_at: Option<Instant>,
+    ttl: Duration,
+}
+
+impl RoomCache {
+    fn new() -> Self {
+        Self {
+            rooms: BTreeMap::new(),
+            fetched_at: None,
+            ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Check if the cache can be served without rereading the network state
+    fn is_fresh(&self) -> bool {
+        !self.rooms.is_empty() && self.fetched_at.is_some_and(|at| at.elapsed() < self.ttl)
+    }
+
+    /// Replace the cached rooms with the rooms of a network read
+    ///
+    /// Existing handles are updated in place so all clones share the new state.
+    fn replace(&mut self, speaker: &Arc<SpeakerConnection>, room_documents: Vec<serde_json::Value>) {
+        let mut previous = std::mem::take(&mut self.rooms);
+        for room_json in room_documents {
+            let room_id = room_json
+                .get("id")
+                .and_then(|v| v.as_str())
+                .and_then(|s| uuid::Uuid::parse_str(s).ok());
+
+            if let Some(existing) = room_id.and_then(|id| previous.remove(&id)) {
+                match existing.update_from_json(room_json) {
+                    Ok(()) => {
+                        self.rooms.insert(existing.id(), existing);
+                    }
+                    Err(e) => tracing::warn!("Failed to parse room: {}", e),
+                }
+                continue;
+            }
+
+            match Room::new(speaker.clone(), room_json) {
+                Ok(room) => {
+                    self.rooms.insert(room.id(), room);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to parse room: {}", e);
+                }
+            }
+        }
+
+        self.fetched_at = Some(Instant::now());
+    }
+
+    /// Apply a room update from the state subscription
+    fn apply_update(&mut self, speaker: &Arc<SpeakerConnection>, room_json: serde_json::Value) {
+        let room_id = room_json
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(|s| uuid::Uuid::parse_str(s).ok());
+
+        let result = match room_id.and_then(|id| self.rooms.get(&id)) {
+            Some(room) => room.update_from_json(room_json),
+            None => Room::new(speaker.clone(), room_json).map(|room| {
+                self.rooms.insert(room.id(), room);
+            }),
+        };
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to apply room update: {}", e);
+        }
+    }
+}
+
+/// Keep the room cache current from the state subscription
+async fn keep_cache_current(speaker: Arc<SpeakerConnection>, cache: Arc<Mutex<RoomCache>>) {
+    let mut receiver = match speaker.subscribe_state().await {
+        Ok(receiver) => receiver,
+        Err(e) => {
+            tracing::warn!("Failed to subscribe for room cache updates: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        match receiver.recv().await {
+            Ok(StateUpdate::RoomUpdate(room_json)) => {
+                cache.lock().unwrap().apply_update(&speaker, Arc::unwrap_or_clone(room_json));
+            }
+            Ok(StateUpdate::Resync(_)) => {
+                tracing::debug!("Room cache subscription resynced, invalidating");
+                cache.lock().unwrap().fetched_at = None;
+            }
+            Ok(_) => {}
+            Err(AscendError::ChannelError(e)) => {
+                // Updates were missed; force the next lookup to reread the state
+                tracing::debug!("Room cache subscription lagged ({}), invalidating", e);
+                cache.lock().unwrap().fetched_at = None;
+            }
+            Err(_) => break,
+        }
+    }
+}
 
 /// Client for connecting to Dutch and Dutch Ascend speakers
 ///
@@ -10,13 +129,20 @@ use std::sync::Arc;
 /// and provides access to room controls and state subscriptions.
 pub struct AscendClient {
     speaker: Arc<SpeakerConnection>,
+    /// Rooms handed out by this client
+    cache: Arc<Mutex<RoomCache>>,
+    /// Background task applying subscription updates to the cache
+    cache_task: tokio::task::JoinHandle<()>,
 }
 
 impl AscendClient {
-    /// Connect directly to a speaker at the given IP address and port
+    /// Connect directly to a speaker at the given IP address or host name and port
     ///
     /// This establishes a WebSocket connection to the speaker's local API.
-    /// The default port is 8768.
+    /// The default port is 8768. Host names such as `ascend-living.local` are
+    /// resolved via DNS, and when they have several IPv4 and IPv6 addresses,
+    /// connection attempts are raced so an unreachable one doesn't hold up the
+    /// others.
     ///
     /// # Example
     ///
@@ -33,19 +159,257 @@ impl AscendClient {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn connect(master_ip: impl Into<String>, port: u16) -> Result<Self> {
-        let speaker = SpeakerConnection::connect(master_ip.into(), port).await?;
+    pub async fn connect(host: impl Into<String>, port: u16) -> Result<Self> {
+        Self::connect_with(host, port, ConnectOptions::default()).await
+    }
 
-        Ok(Self {
-            speaker: Arc::new(speaker),
-        })
+    /// Connect to a speaker with custom timeouts, keepalive, and reconnect policy
+    ///
+    /// Fails with `AscendError::Timeout` if the connection is not established
+    /// within `options.connect_timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, ConnectOptions, ReconnectPolicy};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let options = ConnectOptions {
+    ///         connect_timeout: Duration::from_secs(3),
+    ///         keepalive: Some(Duration::from_secs(30)),
+    ///         reconnect: ReconnectPolicy::Backoff {
+    ///             initial: Duration::from_millis(500),
+    ///             max: Duration::from_secs(30),
+    ///         },
+    ///         ..Default::default()
+    ///     };
+    ///     let client = AscendClient::connect_with("192.168.1.100", 8768, options).await?;
+    ///     println!("{} rooms", client.rooms().await?.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn connect_with(
+        host: impl Into<String>,
+        port: u16,
+        options: ConnectOptions,
+    ) -> Result<Self> {
+        let speaker = Arc::new(SpeakerConnection::connect_with(host.into(), port, options).await?);
+
+        // Detect in the background so firmware ignoring the version read
+        // doesn't hold up the connect for a full request timeout
+        let detecting = speaker.clone();
+        tokio::spawn(async move {
+            detecting.detect_capabilities().await;
+        });
+
+        Ok(Self::from_shared_speaker(speaker))
+    }
+
+    /// Connect to a speaker defined in a configuration file
+    ///
+    /// Requires the `config` feature; see [`crate::config::load`] for an example.
+    #[cfg(feature = "config")]
+    pub async fn from_config_entry(entry: &crate::config::SpeakerEntry) -> Result<Self> {
+        Self::connect_with(entry.host.clone(), entry.port, entry.connect_options()).await
+    }
+
+    /// Create a client over a custom transport instead of a WebSocket
+    ///
+    /// Useful for driving the client against an in-memory fake speaker in
+    /// tests; see [`Transport`] for an example. Firmware capabilities are not
+    /// detected, so no feature is rejected as unsupported.
+    pub async fn with_transport(transport: impl Transport, options: ConnectOptions) -> Self {
+        Self::from_speaker(SpeakerConnection::with_transport(transport, options))
+    }
+
+    /// Create a client that replays a recorded session instead of talking to a speaker
+    ///
+    /// Requires the `recorder` feature. Requests are answered with the
+    /// responses recorded for them (see `start_recording`), so parsing of real
+    /// firmware captures can be exercised without hardware.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, ConnectOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::replay("session.jsonl", ConnectOptions::default()).await?;
+    ///     for room in client.rooms().await? {
+    ///         println!("{}: {} dB", room.name(), room.gain().global);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "recorder")]
+    pub async fn replay(path: impl AsRef<std::path::Path>, options: ConnectOptions) -> Result<Self> {
+        let speaker = SpeakerConnection::replay(path, options)?;
+        Ok(Self::from_speaker(speaker))
+    }
+
+    /// Create a client over an existing speaker connection
+    ///
+    /// The connection can be shared with other clients. Firmware capabilities
+    /// are used as already detected on it; see
+    /// `SpeakerConnection::detect_capabilities`.
+    pub async fn from_connection(speaker: Arc<SpeakerConnection>) -> Self {
+        Self::from_shared_speaker(speaker)
+    }
+
+    /// Get the speaker connection this client uses
+    pub fn speaker_connection(&self) -> Arc<SpeakerConnection> {
+        self.speaker.clone()
+    }
+
+    /// Wrap a speaker connection and start keeping the room cache current
+    fn from_speaker(speaker: SpeakerConnection) -> Self {
+        Self::from_shared_speaker(Arc::new(speaker))
+    }
+
+    fn from_shared_speaker(speaker: Arc<SpeakerConnection>) -> Self {
+        let cache = Arc::new(Mutex::new(RoomCache::new()));
+        let cache_task = tokio::spawn(keep_cache_current(speaker.clone(), cache.clone()));
+
+        Self {
+            speaker,
+            cache,
+            cache_task,
+        }
+    }
+
+    /// Connect to a speaker from a connection string
+    ///
+    /// Accepts `ws://host:port`, `host:port`, or a bare host (using the default
+    /// port 8768); see [`SpeakerAddress`]. Hostnames are resolved as by `connect`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect_url("living-room.local:8768").await?;
+    ///     println!("{} rooms", client.rooms().await?.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn connect_url(url: &str) -> Result<Self> {
+        let address: SpeakerAddress = url.parse()?;
+        Self::connect(address.host, address.port).await
     }
 
     /// Get Room interfaces for all rooms in the speaker system
     ///
-    /// This fetches the current network state and returns a vector of
-    /// `Room` instances that can be used to control volume, mute, voicing, etc.
+    /// Returns `Room` instances that can be used to control volume, mute,
+    /// voicing, etc. Rooms are served from the cache (see `rooms_map`), so all
+    /// handles to the same room share its state.
     pub async fn rooms(&self) -> Result<Vec<Room>> {
+        Ok(self.rooms_map().await?.into_values().collect())
+    }
+
+    /// Get all rooms in the speaker system keyed by room ID
+    ///
+    /// Rooms are cached and kept current by the state subscription, so repeated
+    /// lookups don't reread the network state. The cache is reread when it is
+    /// older than the TTL (see `set_cache_ttl`) or after `invalidate_cache`.
+    pub async fn rooms_map(&self) -> Result<BTreeMap<RoomId, Room>> {
+        {
+            let cache = self.cache.lock().unwrap();
+            if cache.is_fresh() {
+                return Ok(cache.rooms.clone());
+            }
+        }
+
+        let room_documents = self.fetch_room_documents().await?;
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.replace(&self.speaker, room_documents);
+
+        if cache.rooms.is_empty() {
+            return Err(AscendError::InvalidResponse("No rooms found in network state".to_string()));
+        }
+
+        Ok(cache.rooms.clone())
+    }
+
+    /// Force the next room lookup to reread the network state
+    pub fn invalidate_cache(&self) {
+        self.cache.lock().unwrap().fetched_at = None;
+    }
+
+    /// Set how long cached rooms are served without rereading the network state
+    ///
+    /// The default is 30 seconds.
+    pub fn set_cache_ttl(&self, ttl: Duration) {
+        self.cache.lock().unwrap().ttl = ttl;
+    }
+
+    /// Get the only room in the speaker system
+    ///
+    /// Returns `AscendError::AmbiguousRoom` if the system has more than one room;
+    /// use `room_by_id` or `room_by_name` in that case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let room = client.room().await?;
+    ///     room.set_gain(-20.0).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn room(&self) -> Result<Room> {
+        let mut rooms = self.rooms().await?;
+        if rooms.len() > 1 {
+            let names: Vec<String> = rooms.iter().map(|room| room.name()).collect();
+            return Err(AscendError::AmbiguousRoom(format!(
+                "{} rooms available ({}); use room_by_id or room_by_name",
+                rooms.len(),
+                names.join(", ")
+            )));
+        }
+
+        // rooms() errors when there are no rooms
+        Ok(rooms.remove(0))
+    }
+
+    /// Get a room by its ID
+    pub async fn room_by_id(&self, room_id: RoomId) -> Result<Room> {
+        self.rooms()
+            .await?
+            .into_iter()
+            .find(|room| room.id() == room_id)
+            .ok_or_else(|| AscendError::RoomNotFound(room_id.to_string()))
+    }
+
+    /// Get a room by its name
+    pub async fn room_by_name(&self, name: &str) -> Result<Room> {
+        self.rooms()
+            .await?
+            .into_iter()
+            .find(|room| room.name() == name)
+            .ok_or_else(|| AscendError::RoomNotFound(name.to_string()))
+    }
+
+    /// Refresh every room from a single network state read
+    ///
+    /// All handles previously returned by this client observe the refreshed state.
+    pub async fn refresh_all(&self) -> Result<()> {
+        let room_documents = self.fetch_room_documents().await?;
+        self.cache.lock().unwrap().replace(&self.speaker, room_documents);
+        Ok(())
+    }
+
+    /// Fetch the network state and extract the room documents it contains
+    async fn fetch_room_documents(&self) -> Result<Vec<serde_json::Value>> {
         // Get network state from speaker
         let data = self.speaker.request_network_state().await?;
 
@@ -59,33 +423,427 @@ impl AscendClient {
             .ok_or_else(|| AscendError::InvalidResponse("State is not an object".to_string()))?;
 
         // Find all room entries (where data.type == "room")
-        let mut rooms = Vec::new();
+        let mut room_documents = Vec::new();
         for (_state_id, state_entry) in state_obj {
             if let Some(entry_data) = state_entry.get("data") {
                 if entry_data.get("type").and_then(|v| v.as_str()) == Some("room") {
-                    match Room::new(self.speaker.clone(), entry_data.clone()) {
-                        Ok(room) => {
-                            rooms.push(room);
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to parse room: {}", e);
-                        }
-                    }
+                    room_documents.push(entry_data.clone());
                 }
             }
         }
 
-        if rooms.is_empty() {
-            return Err(AscendError::InvalidResponse("No rooms found in network state".to_string()));
+        Ok(room_documents)
+    }
+
+    /// Get all devices in the speaker system
+    ///
+    /// Devices are parsed from the device entries of the network state.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     for (device_id, device) in client.devices().await? {
+    ///         println!("{}: {} {:?}", device_id, device.name, device.tags);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn devices(&self) -> Result<BTreeMap<DeviceId, Device>> {
+        self.speaker.request_devices().await
+    }
+
+    /// List every entry of the network state with its type and raw data
+    ///
+    /// Includes entity types this crate doesn't model yet; changes to those
+    /// arrive as `StateUpdate::UnknownEntity`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     for entity in client.entities().await? {
+    ///         println!("{} ({})", entity.id, entity.entity_type);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn entities(&self) -> Result<Vec<Entity>> {
+        self.speaker.request_entities().await
+    }
+
+    /// Get the controllable rooms and devices from the targets endpoint
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let targets = client.targets().await?;
+    ///     println!("{} rooms, {} devices", targets.rooms.len(), targets.devices.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn targets(&self) -> Result<TargetsResponse> {
+        self.speaker.request_targets().await
+    }
+
+    /// Get the address of the speaker this client is connected to
+    pub fn speaker_address(&self) -> SpeakerAddress {
+        SpeakerAddress {
+            host: self.speaker.ip().to_string(),
+            port: self.speaker.port(),
         }
+    }
+
+    /// Get system information: master address, API version, and device details
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let info = client.system_info().await?;
+    ///     println!("Master: {} (API {:?})", info.master_ip, info.api_version);
+    ///     for (device_id, device) in &info.devices {
+    ///         println!("{}: {:?} serial {:?} firmware {:?}",
+    ///             device_id, device.model(), device.serial, device.firmware_version);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn system_info(&self) -> Result<SystemInfo> {
+        let devices = self.devices().await?;
+
+        // Older firmware may not expose the version endpoint
+        let request = Request::new("version", Method::Read);
+        let api_version = match self.speaker.connection().send_request(request).await {
+            Ok(response) => response
+                .data
+                .as_ref()
+                .and_then(|data| data.get("api"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            Err(e) => {
+                tracing::debug!("Failed to read API version: {}", e);
+                None
+            }
+        };
+
+        Ok(SystemInfo {
+            master_ip: self.speaker.ip().to_string(),
+            port: self.speaker.port(),
+            api_version,
+            devices,
+        })
+    }
+
+    /// Start recording every request, response, and notification to a JSONL file
+    ///
+    /// Requires the `recorder` feature. The recording can be toggled at any
+    /// time; see [`crate::recorder::RecordedMessage`] for the line format.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     client.start_recording("session.jsonl").await?;
+    ///     client.rooms().await?;
+    ///     client.stop_recording().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "recorder")]
+    pub async fn start_recording(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.speaker.connection().start_recording(path).await
+    }
+
+    /// Stop recording, if a recording is in progress
+    #[cfg(feature = "recorder")]
+    pub async fn stop_recording(&self) {
+        self.speaker.connection().stop_recording().await
+    }
 
-        Ok(rooms)
+    /// Read the complete network state document
+    ///
+    /// Returns the raw `network` document with every room and device entry,
+    /// including fields this crate doesn't parse. Useful for debugging and for
+    /// finding out what new firmware reports. Bypasses the room cache.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let state = client.network_state().await?;
+    ///     std::fs::write("network.json", serde_json::to_string_pretty(&state)?)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn network_state(&self) -> Result<serde_json::Value> {
+        self.speaker.request_network_state().await
+    }
+
+    /// Get the unknown room document fields encountered so far
+    ///
+    /// Paths such as `room.newSetting` name fields that newer firmware sends
+    /// but this crate does not understand yet; include them when reporting
+    /// firmware compatibility issues. See `ConnectOptions::parse_mode` for
+    /// rejecting such documents instead.
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.speaker.connection().unknown_fields()
+    }
+
+    /// Get the features supported by the speaker's firmware
+    ///
+    /// Capabilities are detected in the background after connecting. Returns
+    /// `None` until then, or if they could not be determined (e.g. firmware
+    /// without a `version` endpoint, or replayed sessions), in which case methods
+    /// do not check for support before sending requests. Otherwise methods
+    /// needing an unsupported feature fail with `AscendError::Unsupported`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let room = client.room().await?;
+    ///     if client.capabilities().is_some_and(|c| c.supports_linear_phase) {
+    ///         room.set_linear_phase(true).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.speaker.capabilities()
+    }
+
+    /// Measure the round-trip time to the speaker
+    ///
+    /// Sends a lightweight `version` read and times the response. An API error
+    /// reply still counts as a round trip, since the speaker answered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let rtt = client.ping().await?;
+    ///     println!("RTT: {:.1} ms", rtt.as_secs_f64() * 1000.0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        let request = Request::new("version", Method::Read);
+        match self.speaker.connection().send_request(request).await {
+            Ok(_) | Err(AscendError::ApiError { .. }) => Ok(start.elapsed()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get a handle for controlling a single device
+    ///
+    /// Returns `AscendError::DeviceNotFound` if the device is not part of the
+    /// speaker system.
+    pub async fn device(&self, device_id: impl Into<DeviceId>) -> Result<DeviceHandle> {
+        let device_id = device_id.into();
+        let mut devices = self.speaker.request_devices().await?;
+        let device = devices
+            .remove(&device_id)
+            .ok_or_else(|| AscendError::DeviceNotFound(device_id.clone()))?;
+
+        Ok(DeviceHandle::new(self.speaker.clone(), device_id, device))
+    }
+
+    /// Send a raw request and return the full response
+    ///
+    /// This reaches endpoints the high-level API doesn't cover yet. API errors
+    /// in the response are returned as `AscendError::ApiError`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, Method, TargetType};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let room = client.room().await?;
+    ///     let response = client
+    ///         .request("gain2", Method::Read, Some((TargetType::Room, room.id().to_string())), None)
+    ///         .await?;
+    ///     println!("{:?}", response.data);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn request(
+        &self,
+        endpoint: impl Into<String>,
+        method: Method,
+        target: Option<(TargetType, String)>,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let mut request = Request::new(endpoint, method);
+        if let Some((target_type, target)) = target {
+            request = request.with_target(target_type, target);
+        }
+        if let Some(data) = data {
+            request = request.with_data(data);
+        }
+
+        self.send(request).await
+    }
+
+    /// Send a prebuilt request and return the full response
+    pub async fn send(&self, request: Request) -> Result<Response> {
+        self.speaker.connection().send_request(request).await
+    }
+
+    /// Mute every room behind this connection
+    ///
+    /// Rooms are muted concurrently; the returned map holds the result per room.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     for (room_id, result) in client.mute_all().await? {
+    ///         if let Err(e) = result {
+    ///             eprintln!("Failed to mute {}: {}", room_id, e);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn mute_all(&self) -> Result<BTreeMap<RoomId, Result<()>>> {
+        self.for_each_room(|room| async move { room.set_mute(true).await }).await
+    }
+
+    /// Unmute every room behind this connection
+    pub async fn unmute_all(&self) -> Result<BTreeMap<RoomId, Result<()>>> {
+        self.for_each_room(|room| async move { room.set_mute(false).await }).await
+    }
+
+    /// Put every room behind this connection into standby
+    pub async fn standby_all(&self) -> Result<BTreeMap<RoomId, Result<()>>> {
+        self.for_each_room(|room| async move { room.set_standby(true).await }).await
+    }
+
+    /// Run a command against every room concurrently, collecting per-room results
+    async fn for_each_room<F, Fut>(&self, command: F) -> Result<BTreeMap<RoomId, Result<()>>>
+    where
+        F: Fn(Room) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let rooms = self.rooms().await?;
+        let results = join_all(rooms.into_iter().map(|room| {
+            let room_id = room.id();
+            let fut = command(room);
+            async move { (room_id, fut.await) }
+        }))
+        .await;
+
+        Ok(results.into_iter().collect())
+    }
+
+    /// Tear down and re-establish the connection to the speaker
+    ///
+    /// Subscriptions are restored on the new connection and every known room is
+    /// refreshed, so the client is usable again once this resolves. Existing
+    /// `Room` handles and state receivers keep working. Useful for recovery
+    /// when no reconnect policy was configured (see `connect_with`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, ConnectionStatus};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let mut status = client.connection_status();
+    ///
+    ///     while status.changed().await.is_ok() {
+    ///         if *status.borrow_and_update() == ConnectionStatus::Disconnected {
+    ///             client.reconnect().await?;
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn reconnect(&self) -> Result<()> {
+        self.speaker.reconnect().await?;
+        self.refresh_all().await
+    }
+
+    /// Watch the health of the link to the speaker
+    ///
+    /// The status changes without any probe requests being sent, so a daemon
+    /// can report link health cheaply. `Reconnecting` is only reported when
+    /// the client was connected with a reconnect policy (see `connect_with`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, ConnectionStatus};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let mut status = client.connection_status();
+    ///
+    ///     while status.changed().await.is_ok() {
+    ///         if *status.borrow() == ConnectionStatus::Disconnected {
+    ///             eprintln!("Lost connection to speaker");
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.speaker.connection_status()
     }
 
     /// Subscribe to state updates from the speaker system
     ///
     /// Returns a receiver that will yield state updates as they occur.
-    /// Multiple subscriptions can be active simultaneously.
+    /// Multiple subscriptions can be active simultaneously. Fails if the
+    /// speaker rejects the subscription or doesn't acknowledge it within the
+    /// request timeout.
     ///
     /// # Example
     ///
@@ -107,4 +865,18 @@ impl AscendClient {
     pub async fn subscribe_state(&self) -> Result<StateReceiver> {
         self.speaker.subscribe_state().await
     }
+
+    /// Subscribe to state updates for a single room
+    ///
+    /// Updates for other rooms on the same speaker system are filtered out.
+    /// Equivalent to `Room::subscribe` when only the room ID is at hand.
+    pub async fn subscribe_room(&self, room_id: RoomId) -> Result<RoomReceiver> {
+        self.speaker.subscribe_room(room_id).await
+    }
+}
+
+impl Drop for AscendClient {
+    fn drop(&mut self) {
+        self.cache_task.abort();
+    }
 }
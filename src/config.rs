@@ -0,0 +1,212 @@
+//! Static speaker configuration from TOML files (requires the `config` feature)
+//!
+//! Lets headless daemons connect to a fixed set of speakers without discovery:
+//!
+//! ```toml
+//! [defaults]
+//! max_gain = -10.0
+//!
+//! [bridge]
+//! rpc_listen = "127.0.0.1:4000"
+//!
+//! [[speaker]]
+//! name = "studio"
+//! host = "192.168.1.100"
+//! labels = ["mixing", "ground-floor"]
+//!
+//! [[speaker]]
+//! name = "lounge"
+//! host = "lounge-speakers.local"
+//! port = 8768
+//! max_gain = -20.0
+//! ```
+
+use crate::connection::ConnectOptions;
+use crate::error::{AscendError, Result};
+use crate::types::{GainDb, SpeakerAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Settings applied to every speaker that doesn't override them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Defaults {
+    /// Highest global gain in dB room setters will send; see `ConnectOptions::max_gain`
+    #[serde(default)]
+    pub max_gain: Option<GainDb>,
+}
+
+/// Settings of the bridges a daemon serves its speakers on
+///
+/// Only the JSON-RPC server is configurable here; webhooks and Home
+/// Assistant discovery are set up in code.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BridgeSettings {
+    /// Address to serve JSON-RPC on, e.g. `127.0.0.1:4000`, or `None` for no
+    /// server; see `rpc::RpcServer::serve_tcp`
+    #[serde(default)]
+    pub rpc_listen: Option<String>,
+}
+
+/// A speaker to connect to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeakerEntry {
+    /// Unique name used to refer to the speaker
+    pub name: String,
+
+    /// IP address or hostname of the speaker
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Free-form labels for grouping speakers, e.g. by floor or purpose
+    #[serde(default)]
+    pub labels: Vec<String>,
+
+    /// Overrides [`Defaults::max_gain`] for this speaker
+    #[serde(default)]
+    pub max_gain: Option<GainDb>,
+}
+
+fn default_port() -> u16 {
+    SpeakerAddress::DEFAULT_PORT
+}
+
+impl SpeakerEntry {
+    /// Get the address of the speaker
+    pub fn address(&self) -> SpeakerAddress {
+        SpeakerAddress {
+            host: self.host.clone(),
+            port: self.port,
+        }
+    }
+
+    /// Check if the speaker has the given label
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.iter().any(|l| l == label)
+    }
+
+    /// Get the connection options for this speaker
+    pub fn connect_options(&self) -> ConnectOptions {
+        ConnectOptions {
+            max_gain: self.max_gain,
+            ..Default::default()
+        }
+    }
+}
+
+/// Contents of a configuration file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Defaults,
+
+    /// Bridge settings, from the `[bridge]` table
+    #[serde(default)]
+    pub bridge: BridgeSettings,
+
+    /// Speakers, from the `[[speaker]]` tables
+    #[serde(default, rename = "speaker")]
+    pub speakers: Vec<SpeakerEntry>,
+}
+
+impl Config {
+    /// Get a speaker by name
+    pub fn speaker(&self, name: &str) -> Option<&SpeakerEntry> {
+        self.speakers.iter().find(|speaker| speaker.name == name)
+    }
+
+    /// Get the speakers with the given label
+    pub fn speakers_with_label<'a>(&'a self, label: &'a str) -> impl Iterator<Item = &'a SpeakerEntry> + 'a {
+        self.speakers.iter().filter(move |speaker| speaker.has_label(label))
+    }
+
+    /// Check names and addresses, and apply the defaults to every speaker
+    fn resolve(mut self) -> Result<Self> {
+        let mut names = HashSet::new();
+        for speaker in &mut self.speakers {
+            if speaker.host.trim().is_empty() {
+                return Err(AscendError::InvalidArgument(format!(
+                    "Speaker '{}' has no host",
+                    speaker.name
+                )));
+            }
+            if !names.insert(speaker.name.clone()) {
+                return Err(AscendError::InvalidArgument(format!(
+                    "Speaker '{}' is defined more than once",
+                    speaker.name
+                )));
+            }
+            speaker.max_gain = speaker.max_gain.or(self.defaults.max_gain);
+        }
+        Ok(self)
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = AscendError;
+
+    /// Parse a configuration from TOML
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::config::Config;
+    /// use dutchdutch_ascend::GainDb;
+    ///
+    /// let config: Config = r#"
+    ///     [defaults]
+    ///     max_gain = -10.0
+    ///
+    ///     [bridge]
+    ///     rpc_listen = "127.0.0.1:4000"
+    ///
+    ///     [[speaker]]
+    ///     name = "studio"
+    ///     host = "192.168.1.100"
+    /// "#
+    /// .parse()
+    /// .unwrap();
+    ///
+    /// let studio = config.speaker("studio").unwrap();
+    /// assert_eq!(studio.port, 8768);
+    /// assert_eq!(studio.max_gain, Some(GainDb::new(-10.0)));
+    /// assert_eq!(config.bridge.rpc_listen.as_deref(), Some("127.0.0.1:4000"));
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        toml::from_str::<Self>(s)
+            .map_err(|e| AscendError::InvalidArgument(format!("Invalid configuration: {}", e)))?
+            .resolve()
+    }
+}
+
+/// Load a configuration file
+///
+/// Speakers without their own `max_gain` inherit the one from `[defaults]`.
+/// Fails if the file can't be read, isn't valid TOML, or defines a speaker
+/// name twice.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::AscendClient;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let config = dutchdutch_ascend::config::load("/etc/ascend/speakers.toml")?;
+///
+///     for entry in config.speakers_with_label("mixing") {
+///         let client = AscendClient::from_config_entry(entry).await?;
+///         println!("{}: {} rooms", entry.name, client.rooms().await?.len());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn load(path: impl AsRef<Path>) -> Result<Config> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)?;
+    toml::from_str::<Config>(&contents)
+        .map_err(|e| AscendError::InvalidArgument(format!("Invalid configuration in {}: {}", path.display(), e)))?
+        .resolve()
+}
@@ -1,114 +1,689 @@
-use crate::error::{AscendError, Result};
-use crate::protocol::{Request, Response};
-use crate::subscription::StateUpdate;
-use futures_util::{SinkExt, StreamExt};
+use crate::error::{AscendError, RequestContext, Result};
+use crate::protocol::{Method, Request, RequestMeta, Response, TargetType, TypedEndpoint};
+#[cfg(feature = "recorder")]
+use crate::recorder::{Direction, ReplaySession, SessionRecorder};
+use crate::subscription::{ChangeSource, StandbyCause, StateUpdate};
+use crate::transport::{connect_tcp, Transport, WebSocketTransport};
+use crate::room::{merge_room_state_from_json, parse_room_state_from_json, ParseContext, ParseMode, RoomField, RoomState};
+use crate::types::{Device, GainDb, ProtectionEvent, ProtectionKind, RoomId};
+use futures_util::future::join_all;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, OwnedMutexGuard};
 use tokio::time::timeout;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::client_async_tls;
+use tokio_tungstenite::tungstenite::http::Uri;
 use uuid::Uuid;
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const SUBSCRIPTION_CAPACITY: usize = 100;
+/// How long after a command to a room its changes are attributed to this connection
+const LOCAL_CHANGE_WINDOW: Duration = Duration::from_secs(3);
+
+/// Options controlling how a connection is established and maintained
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::{ConnectOptions, ReconnectPolicy};
+/// use std::time::Duration;
+///
+/// let options = ConnectOptions {
+///     connect_timeout: Duration::from_secs(2),
+///     keepalive: Some(Duration::from_secs(30)),
+///     tcp_keepalive: Some(Duration::from_secs(30)),
+///     reconnect: ReconnectPolicy::Backoff {
+///         initial: Duration::from_secs(1),
+///         max: Duration::from_secs(30),
+///     },
+///     ..Default::default()
+/// };
+/// # let _ = options;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    /// Maximum time to establish the WebSocket connection
+    pub connect_timeout: Duration,
+
+    /// Maximum time to wait for the response to a request
+    pub request_timeout: Duration,
+
+    /// Interval between WebSocket pings, or `None` to disable keepalive
+    ///
+    /// If nothing, not even a pong, arrives between two pings, the connection
+    /// is assumed to be half-open and is dropped (and reconnected according
+    /// to `reconnect`). A speaker that silently left the network is then
+    /// noticed within two intervals rather than on the next request timeout.
+    /// Defaults to `None`; short intervals also drop connections that only
+    /// stalled briefly, e.g. during a WiFi hiccup, so 30 seconds or more is
+    /// a reasonable choice.
+    pub keepalive: Option<Duration>,
+
+    /// Idle time before the OS starts sending TCP keepalive probes, and the
+    /// time between probes, or `None` to leave the socket's defaults
+    ///
+    /// See `WebSocketTransport::set_tcp_keepalive`. Defaults to `None`.
+    pub tcp_keepalive: Option<Duration>,
+
+    /// What to do when the connection drops
+    pub reconnect: ReconnectPolicy,
+
+    /// Number of state updates buffered per subscriber before it lags
+    pub subscription_capacity: usize,
+
+    /// What a state subscriber sees when it falls behind
+    pub lag_policy: LagPolicy,
+
+    /// How room documents with unknown or malformed fields are handled
+    pub parse_mode: ParseMode,
+
+    /// Send each room's commands one at a time, in the order they were made
+    ///
+    /// Without this, concurrent setters on the same room (for example from
+    /// several clones of a `Room`) may reach the speaker in any order. When
+    /// enabled, a command waits for the previous one on that room to be
+    /// acknowledged, so the last call made is the last value applied.
+    pub serialize_room_commands: bool,
+
+    /// Highest global gain in dB that room setters will send
+    ///
+    /// Becomes the initial safety limit of every room on the connection (see
+    /// `Room::set_safety_limit`), so requests above it are rejected with
+    /// `AscendError::SafetyLimit`, e.g. to protect a room from a mistyped
+    /// volume. `None` leaves the speaker's own limits in charge.
+    pub max_gain: Option<GainDb>,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: CONNECT_TIMEOUT,
+            request_timeout: REQUEST_TIMEOUT,
+            keepalive: None,
+            tcp_keepalive: None,
+            reconnect: ReconnectPolicy::Never,
+            subscription_capacity: SUBSCRIPTION_CAPACITY,
+            lag_policy: LagPolicy::Error,
+            parse_mode: ParseMode::Lenient,
+            serialize_room_commands: false,
+            max_gain: None,
+        }
+    }
+}
+
+/// How a lagging state subscriber recovers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Report the lag as a `ChannelError` and continue with the oldest buffered update
+    #[default]
+    Error,
+
+    /// Refetch the full network state and deliver it as `StateUpdate::Resync`
+    Resync,
+}
+
+/// Automatic reconnection policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectPolicy {
+    /// Stay disconnected once the connection drops
+    Never,
+
+    /// Reconnect with exponential backoff between attempts
+    Backoff {
+        /// Delay before the first attempt
+        initial: Duration,
+        /// Upper bound for the delay between attempts
+        max: Duration,
+    },
+}
+/// Health of the link to a speaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// The WebSocket is open
+    Connected,
+    /// The WebSocket closed and will not be reopened automatically
+    Disconnected,
+    /// The WebSocket closed and a reconnect is in progress
+    Reconnecting,
+}
+
+/// Transmission priority of an outgoing request
+///
+/// Queued interactive messages are always written to the socket before queued
+/// bulk messages, so a large transfer doesn't delay a volume change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// User-facing commands such as gain and mute
+    #[default]
+    Interactive,
+
+    /// Background operations such as network state refetches
+    Bulk,
+}
+
+/// Sending ends of the outgoing message queues
+struct OutgoingSenders {
+    interactive: mpsc::UnboundedSender<String>,
+    bulk: mpsc::UnboundedSender<String>,
+}
+
+/// Receiving ends of the outgoing message queues
+struct OutgoingQueues {
+    interactive: mpsc::UnboundedReceiver<String>,
+    bulk: mpsc::UnboundedReceiver<String>,
+}
+
+impl OutgoingQueues {
+    /// Create a connected pair of senders and queues
+    fn new() -> (OutgoingSenders, Self) {
+        let (interactive_tx, interactive) = mpsc::unbounded_channel();
+        let (bulk_tx, bulk) = mpsc::unbounded_channel();
+        (
+            OutgoingSenders {
+                interactive: interactive_tx,
+                bulk: bulk_tx,
+            },
+            Self { interactive, bulk },
+        )
+    }
+
+    /// Receive the next outgoing message, interactive ones first
+    ///
+    /// Returns `None` once both queues are closed.
+    async fn recv(&mut self) -> Option<String> {
+        tokio::select! {
+            biased;
+            Some(message) = self.interactive.recv() => Some(message),
+            Some(message) = self.bulk.recv() => Some(message),
+            else => None,
+        }
+    }
+}
 
 /// WebSocket connection state
+///
+/// Each part has its own lock, held only briefly and never across an await,
+/// so inbound notifications and outgoing requests don't wait on each other.
 struct ConnectionState {
     /// Pending requests waiting for responses
-    pending_requests: HashMap<Uuid, oneshot::Sender<Response>>,
-    /// Channel for sending outgoing messages
-    ws_tx: mpsc::UnboundedSender<Message>,
+    pending_requests: std::sync::Mutex<HashMap<Uuid, oneshot::Sender<Response>>>,
+    /// Channels for sending outgoing messages, replaced on reconnect
+    outgoing: std::sync::Mutex<OutgoingSenders>,
+    /// Last room state seen per room, used to detect changes
+    rooms: std::sync::Mutex<HashMap<RoomId, RoomState>>,
+    /// Subscribe requests sent on this connection, replayed after reconnecting
+    subscriptions: std::sync::Mutex<Vec<Request>>,
+    /// When each room was last sent a command, for attributing its changes
+    recent_commands: std::sync::Mutex<HashMap<RoomId, Instant>>,
+    /// Last sequence number seen per notification type, for detecting gaps
+    notify_seqs: std::sync::Mutex<HashMap<String, u64>>,
+    /// Maximum time to wait for the network state refetched after a gap
+    request_timeout: Duration,
+    /// Parse settings for room documents
+    parse_context: Arc<ParseContext>,
+    /// Session recorder, when recording is active
+    #[cfg(feature = "recorder")]
+    recorder: std::sync::Mutex<Option<SessionRecorder>>,
+    /// Commands sent on this connection, for journals
+    #[cfg(feature = "journal")]
+    commands_tx: broadcast::Sender<Request>,
+}
+
+impl ConnectionState {
+    /// Queue a serialized message for the WebSocket
+    fn send_text(&self, json: String, priority: Priority) -> Result<()> {
+        #[cfg(feature = "recorder")]
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record(Direction::Sent, &json);
+        }
+
+        let outgoing = self.outgoing.lock().unwrap();
+        let tx = match priority {
+            Priority::Interactive => &outgoing.interactive,
+            Priority::Bulk => &outgoing.bulk,
+        };
+        tx.send(json)
+            .map_err(|_| AscendError::ConnectionClosed)
+    }
+
+    /// Remember a command to a room, so the changes it causes count as local
+    fn note_command(&self, meta: &RequestMeta) {
+        if matches!(meta.method, Method::Read | Method::Subscribe) || meta.target_type != Some(TargetType::Room) {
+            return;
+        }
+        if let Some(room_id) = meta.target.as_deref().and_then(|target| Uuid::parse_str(target).ok()) {
+            self.recent_commands.lock().unwrap().insert(room_id, Instant::now());
+        }
+    }
+
+    /// Pass a command on to running journals
+    #[cfg(feature = "journal")]
+    fn journal_command(&self, request: &Request) {
+        if matches!(request.meta.method, Method::Read | Method::Subscribe) || self.commands_tx.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.commands_tx.send(request.clone());
+    }
+
+    /// Attribute a change to a room to this connection or another controller
+    fn change_source(&self, room_id: RoomId) -> ChangeSource {
+        let mut recent_commands = self.recent_commands.lock().unwrap();
+        recent_commands.retain(|_, sent| sent.elapsed() <= LOCAL_CHANGE_WINDOW);
+        if recent_commands.contains_key(&room_id) {
+            ChangeSource::Local
+        } else {
+            ChangeSource::External
+        }
+    }
+
+    /// Check a notification's sequence number, returning whether notifications were skipped
+    ///
+    /// Numbering restarting from a lower value is not a gap.
+    fn notify_gap(&self, response: &Response) -> bool {
+        let Some(seq) = response.meta.seq else {
+            return false;
+        };
+        let kind = response.meta.response_type.clone().unwrap_or_default();
+        let previous = self.notify_seqs.lock().unwrap().insert(kind, seq);
+        previous.is_some_and(|previous| seq > previous + 1)
+    }
+
+    /// Refetch the network state in the background and deliver it as `StateUpdate::Resync`
+    fn resync(&self, subscription_tx: &broadcast::Sender<StateUpdate>) {
+        let request = Request::new("network", Method::Read);
+        let json = match serde_json::to_string(&request) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!("Failed to refetch network state after missed notifications: {}", e);
+                return;
+            }
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending_requests.lock().unwrap().insert(request.id(), tx);
+        if let Err(e) = self.send_text(json, Priority::Bulk) {
+            self.pending_requests.lock().unwrap().remove(&request.id());
+            tracing::warn!("Failed to refetch network state after missed notifications: {}", e);
+            return;
+        }
+
+        let subscription_tx = subscription_tx.clone();
+        let request_timeout = self.request_timeout;
+        tokio::spawn(async move {
+            match timeout(request_timeout, rx).await {
+                Ok(Ok(Response { data: Some(data), .. })) => {
+                    let _ = subscription_tx.send(StateUpdate::Resync(Arc::new(data)));
+                }
+                _ => tracing::warn!("No network state received after missed notifications"),
+            }
+        });
+    }
+
+    /// Cancel all pending requests
+    fn clear_pending(&self) {
+        self.pending_requests.lock().unwrap().clear();
+    }
 }
 
 /// Low-level WebSocket connection handler
 pub struct Connection {
-    state: Arc<Mutex<ConnectionState>>,
+    state: Arc<ConnectionState>,
     /// Broadcast channel for subscription updates (outside mutex to allow non-blocking subscribe)
     subscription_tx: broadcast::Sender<StateUpdate>,
+    /// WebSocket URL, or `None` for a custom transport that can't be reopened
+    url: Option<String>,
+    options: ConnectOptions,
+    /// Parse settings for room documents, shared with `state`
+    parse_context: Arc<ParseContext>,
+    /// Current link status
+    status_tx: watch::Sender<ConnectionStatus>,
+    /// Task driving the transport, aborted when the connection is dropped
+    io_task: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// Per-room command queues, used when `serialize_room_commands` is set
+    room_queues: std::sync::Mutex<HashMap<RoomId, Arc<Mutex<()>>>>,
 }
 
 impl Connection {
-    /// Connect to a WebSocket URL
-    pub async fn connect(url: impl Into<String>) -> Result<Self> {
+    /// Connect to a WebSocket URL with the given options
+    pub async fn connect_with(url: impl Into<String>, options: ConnectOptions) -> Result<Self> {
         let url = url.into();
-        tracing::info!("Connecting to {}", url);
+        let transport = Self::open(&url, &options).await?;
 
-        let (ws_stream, _) = connect_async(&url).await?;
-        let (mut write, mut read) = ws_stream.split();
+        let task_url = url.clone();
+        let task_options = options.clone();
+        Ok(Self::assemble(Some(url), options, move |state, subscription_tx, status_tx, queues| {
+            Some(tokio::spawn(Self::run(
+                task_url,
+                task_options,
+                state,
+                subscription_tx,
+                status_tx,
+                transport,
+                queues,
+            )))
+        }))
+    }
+
+    /// Create a connection over a custom transport
+    ///
+    /// The transport cannot be reopened, so the reconnect policy does not
+    /// apply: once it closes, the connection stays disconnected.
+    pub fn with_transport(transport: impl Transport, options: ConnectOptions) -> Self {
+        let task_options = options.clone();
+        Self::assemble(None, options, move |state, subscription_tx, status_tx, mut queues| {
+            Some(tokio::spawn(async move {
+                Self::serve(&task_options, &state, &subscription_tx, transport, &mut queues).await;
+                state.clear_pending();
+                status_tx.send_replace(ConnectionStatus::Disconnected);
+            }))
+        })
+    }
+
+    /// Create a connection that is already closed
+    ///
+    /// Every request fails with `AscendError::ConnectionClosed`. Does not need
+    /// a Tokio runtime.
+    pub(crate) fn detached(options: ConnectOptions) -> Self {
+        Self::assemble(None, options, |_state, _subscription_tx, status_tx, _queues| {
+            status_tx.send_replace(ConnectionStatus::Disconnected);
+            None
+        })
+    }
 
+    /// Create a connection that answers requests from a recorded session
+    ///
+    /// Each request is answered with the response recorded for the same
+    /// endpoint, method, and target, in recorded order. Recorded notifications
+    /// are delivered once the first subscription is requested. Requests with no
+    /// recorded response fail with an API error.
+    #[cfg(feature = "recorder")]
+    pub fn replay(path: impl AsRef<std::path::Path>, options: ConnectOptions) -> Result<Self> {
+        let session = ReplaySession::load(path.as_ref())?;
+        Ok(Self::with_transport(session, options))
+    }
+
+    /// Set up connection state and channels around an I/O task
+    fn assemble(
+        url: Option<String>,
+        options: ConnectOptions,
+        spawn_io: impl FnOnce(
+            Arc<ConnectionState>,
+            broadcast::Sender<StateUpdate>,
+            watch::Sender<ConnectionStatus>,
+            OutgoingQueues,
+        ) -> Option<tokio::task::JoinHandle<()>>,
+    ) -> Self {
         // Create channels
-        let (ws_tx, mut ws_rx) = mpsc::unbounded_channel::<Message>();
-        let (subscription_tx, _) = broadcast::channel(100);
-
-        let state = Arc::new(Mutex::new(ConnectionState {
-            pending_requests: HashMap::new(),
-            ws_tx,
-        }));
-
-        // Spawn task to forward outgoing messages to WebSocket
-        let write_handle = tokio::spawn(async move {
-            while let Some(msg) = ws_rx.recv().await {
-                if let Err(e) = write.send(msg).await {
-                    tracing::error!("Failed to send message: {}", e);
-                    break;
+        let (outgoing, queues) = OutgoingQueues::new();
+        let (subscription_tx, _) = broadcast::channel(options.subscription_capacity.max(1));
+        let status_tx = watch::Sender::new(ConnectionStatus::Connected);
+        let parse_context = Arc::new(ParseContext::new(options.parse_mode));
+
+        let state = Arc::new(ConnectionState {
+            pending_requests: std::sync::Mutex::new(HashMap::new()),
+            outgoing: std::sync::Mutex::new(outgoing),
+            rooms: std::sync::Mutex::new(HashMap::new()),
+            subscriptions: std::sync::Mutex::new(Vec::new()),
+            recent_commands: std::sync::Mutex::new(HashMap::new()),
+            notify_seqs: std::sync::Mutex::new(HashMap::new()),
+            request_timeout: options.request_timeout,
+            parse_context: parse_context.clone(),
+            #[cfg(feature = "recorder")]
+            recorder: std::sync::Mutex::new(None),
+            #[cfg(feature = "journal")]
+            commands_tx: broadcast::channel(options.subscription_capacity.max(1)).0,
+        });
+
+        let io_task = spawn_io(state.clone(), subscription_tx.clone(), status_tx.clone(), queues);
+
+        Self {
+            state,
+            subscription_tx,
+            url,
+            options,
+            parse_context,
+            status_tx,
+            io_task: std::sync::Mutex::new(io_task),
+            room_queues: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Tear down the WebSocket and establish a new one
+    ///
+    /// Pending requests are cancelled and subscriptions are replayed on the
+    /// new socket. Existing subscription receivers keep working.
+    pub async fn reconnect(&self) -> Result<()> {
+        let Some(url) = &self.url else {
+            return Err(AscendError::InvalidArgument(
+                "Only WebSocket connections can be reopened".to_string(),
+            ));
+        };
+
+        if let Some(io_task) = self.io_task.lock().unwrap().take() {
+            io_task.abort();
+        }
+        self.state.clear_pending();
+        self.status_tx.send_replace(ConnectionStatus::Reconnecting);
+
+        let transport = match Self::open(url, &self.options).await {
+            Ok(transport) => transport,
+            Err(e) => {
+                self.status_tx.send_replace(ConnectionStatus::Disconnected);
+                return Err(e);
+            }
+        };
+
+        let queues = Self::resubscribe(&self.state);
+        *self.io_task.lock().unwrap() = Some(tokio::spawn(Self::run(
+            url.clone(),
+            self.options.clone(),
+            self.state.clone(),
+            self.subscription_tx.clone(),
+            self.status_tx.clone(),
+            transport,
+            queues,
+        )));
+
+        self.status_tx.send_replace(ConnectionStatus::Connected);
+        tracing::info!("Reconnected to {}", url);
+        Ok(())
+    }
+
+    /// Route outgoing messages to a new socket and replay subscriptions
+    ///
+    /// Returns the queues the new socket's writer should drain.
+    fn resubscribe(state: &ConnectionState) -> OutgoingQueues {
+        let (outgoing, queues) = OutgoingQueues::new();
+        *state.outgoing.lock().unwrap() = outgoing;
+        state.notify_seqs.lock().unwrap().clear();
+        let subscriptions = state.subscriptions.lock().unwrap().clone();
+        for mut request in subscriptions {
+            request.meta.id = Uuid::new_v4();
+            match serde_json::to_string(&request) {
+                Ok(json) => {
+                    let _ = state.send_text(json, Priority::Interactive);
                 }
+                Err(e) => tracing::error!("Failed to serialize subscription: {}", e),
             }
-        });
+        }
+        queues
+    }
 
-        // Spawn task to receive and process incoming messages
-        let state_clone = state.clone();
-        let subscription_tx_clone = subscription_tx.clone();
-        tokio::spawn(async move {
-            while let Some(msg_result) = read.next().await {
-                match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if let Err(e) = Self::handle_message(&state_clone, &subscription_tx_clone, text).await {
-                            tracing::error!("Error handling message: {}", e);
-                        }
+    /// Open a WebSocket connection within the connect timeout
+    async fn open(url: &str, options: &ConnectOptions) -> Result<WebSocketTransport> {
+        tracing::info!("Connecting to {}", url);
+
+        let (ws_stream, _) = timeout(options.connect_timeout, async {
+            let (host, port) = Self::host_and_port(url)
+                .ok_or_else(|| AscendError::InvalidArgument(format!("Invalid WebSocket URL: {}", url)))?;
+            let tcp = connect_tcp(&host, port).await?;
+            Ok::<_, AscendError>(client_async_tls(url, tcp).await?)
+        })
+        .await
+        .map_err(|_| AscendError::Timeout { request: None })??;
+        let transport = WebSocketTransport::new(ws_stream);
+        if let Some(keepalive) = options.tcp_keepalive {
+            if let Err(e) = transport.set_tcp_keepalive(keepalive) {
+                tracing::warn!("Failed to enable TCP keepalive: {}", e);
+            }
+        }
+        Ok(transport)
+    }
+
+    /// Get the host and port to open a TCP connection to from a WebSocket URL
+    fn host_and_port(url: &str) -> Option<(String, u16)> {
+        let uri: Uri = url.parse().ok()?;
+        let host = uri.host()?;
+        let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+        let port = match (uri.port_u16(), uri.scheme_str()) {
+            (Some(port), _) => port,
+            (None, Some("wss")) => 443,
+            (None, _) => 80,
+        };
+        Some((host.to_string(), port))
+    }
+
+    /// Drive the WebSocket, reconnecting according to the policy when it drops
+    async fn run(
+        url: String,
+        options: ConnectOptions,
+        state: Arc<ConnectionState>,
+        subscription_tx: broadcast::Sender<StateUpdate>,
+        status_tx: watch::Sender<ConnectionStatus>,
+        mut transport: WebSocketTransport,
+        mut queues: OutgoingQueues,
+    ) {
+        loop {
+            Self::serve(&options, &state, &subscription_tx, transport, &mut queues).await;
+
+            // Connection closed, cancel all pending requests
+            state.clear_pending();
+
+            let ReconnectPolicy::Backoff { initial, max } = options.reconnect else {
+                status_tx.send_replace(ConnectionStatus::Disconnected);
+                break;
+            };
+            status_tx.send_replace(ConnectionStatus::Reconnecting);
+
+            let mut backoff = initial;
+            transport = loop {
+                tracing::info!("Reconnecting to {} in {:?}", url, backoff);
+                tokio::time::sleep(backoff).await;
+
+                match Self::open(&url, &options).await {
+                    Ok(transport) => break transport,
+                    Err(e) => {
+                        tracing::warn!("Reconnect to {} failed: {}", url, e);
+                        backoff = (backoff * 2).min(max);
                     }
-                    Ok(Message::Close(_)) => {
-                        tracing::info!("WebSocket connection closed");
+                }
+            };
+
+            queues = Self::resubscribe(&state);
+            status_tx.send_replace(ConnectionStatus::Connected);
+            tracing::info!("Reconnected to {}", url);
+        }
+    }
+
+    /// Exchange messages over a transport until it closes
+    async fn serve(
+        options: &ConnectOptions,
+        state: &ConnectionState,
+        subscription_tx: &broadcast::Sender<StateUpdate>,
+        mut transport: impl Transport,
+        queues: &mut OutgoingQueues,
+    ) {
+        let mut keepalive = options.keepalive.map(tokio::time::interval);
+        let mut last_ping: Option<Instant> = None;
+
+        loop {
+            tokio::select! {
+                outgoing = queues.recv() => {
+                    let Some(text) = outgoing else {
+                        break;
+                    };
+                    if let Err(e) = transport.send(text).await {
+                        tracing::error!("Failed to send message: {}", e);
                         break;
                     }
-                    Err(e) => {
-                        tracing::error!("WebSocket error: {}", e);
+                }
+                _ = async { keepalive.as_mut().unwrap().tick().await }, if keepalive.is_some() => {
+                    // Nothing received since the previous ping, so the peer is gone
+                    if let (Some(pinged), Some(received)) = (last_ping, transport.last_received()) {
+                        if received < pinged {
+                            tracing::warn!("No answer to keepalive ping, dropping half-open connection");
+                            break;
+                        }
+                    }
+                    last_ping = Some(Instant::now());
+                    if let Err(e) = transport.ping().await {
+                        tracing::error!("Failed to send keepalive ping: {}", e);
                         break;
                     }
-                    _ => {}
+                }
+                incoming = transport.recv() => {
+                    match incoming {
+                        Some(Ok(text)) => {
+                            if let Err(e) = Self::handle_message(state, subscription_tx, text) {
+                                tracing::error!("Error handling message: {}", e);
+                            }
+                        }
+                        None => {
+                            tracing::info!("Connection closed");
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            tracing::error!("Transport error: {}", e);
+                            break;
+                        }
+                    }
                 }
             }
-
-            // Connection closed, cancel all pending requests
-            let mut state = state_clone.lock().await;
-            state.pending_requests.clear();
-            drop(write_handle);
-        });
-
-        Ok(Self {
-            state,
-            subscription_tx,
-        })
+        }
     }
 
     /// Handle an incoming message
-    async fn handle_message(
-        state: &Arc<Mutex<ConnectionState>>,
+    fn handle_message(
+        state: &ConnectionState,
         subscription_tx: &broadcast::Sender<StateUpdate>,
         text: String,
-    ) -> Result<()> {
+    ) -> serde_json::Result<()> {
         tracing::debug!("Received: {}", text);
 
-        let response: Response = serde_json::from_str(&text)?;
+        #[cfg(feature = "recorder")]
+        if let Some(recorder) = state.recorder.lock().unwrap().as_mut() {
+            recorder.record(Direction::Received, &text);
+        }
 
-        let mut state = state.lock().await;
+        let response: Response = serde_json::from_str(&text)?;
 
         // Check if this is a response to a pending request
-        if let Some(tx) = state.pending_requests.remove(&response.meta.id) {
-            // Send response to waiting request
-            let _ = tx.send(response);
-        } else {
-            // This is a subscription update (no matching request ID)
-            if let Some(update) = Self::parse_state_update(&response) {
+        let pending = state.pending_requests.lock().unwrap().remove(&response.meta.id);
+        let is_notify = response.meta.method == Method::Notify;
+        if let Some(tx) = pending {
+            if !is_notify {
+                let _ = tx.send(response);
+                return Ok(());
+            }
+            // A notification under a subscription's ID also acknowledges it
+            let _ = tx.send(response.clone());
+        }
+
+        // This is a subscription update
+        if is_notify && state.notify_gap(&response) {
+            tracing::warn!("Missed notifications from the speaker, refetching network state");
+            state.resync(subscription_tx);
+        }
+        for update in Self::parse_state_updates(&response) {
+            let derived_updates = match &update {
+                StateUpdate::RoomUpdate(room_json) => Self::room_change_updates(state, room_json),
+                _ => Vec::new(),
+            };
+
+            let _ = subscription_tx.send(update);
+            for update in derived_updates {
                 let _ = subscription_tx.send(update);
             }
         }
@@ -116,93 +691,356 @@ impl Connection {
         Ok(())
     }
 
-    /// Parse a response into a state update
-    fn parse_state_update(response: &Response) -> Option<StateUpdate> {
-        use crate::protocol::Method;
-
-        // Check if this is a network subscription notification
-        if response.meta.method == Method::Notify
-            && response.meta.response_type.as_deref() == Some("network") {
-
-            if let Some(data) = &response.data {
-                // Look for data.state
-                if let Some(state) = data.get("state") {
-                    if let Some(state_obj) = state.as_object() {
-                        // Find the first room in the state
-                        for (_state_id, state_entry) in state_obj {
-                            if let Some(entry_data) = state_entry.get("data") {
-                                if entry_data.get("type").and_then(|v| v.as_str()) == Some("room") {
-                                    // Return raw JSON for room updates
-                                    return Some(StateUpdate::RoomUpdate(Box::new(entry_data.clone())));
-                                }
-                            }
-                        }
+    /// Parse a response into state updates
+    ///
+    /// A network notification may carry several rooms and devices; one update
+    /// is produced per entry.
+    fn parse_state_updates(response: &Response) -> Vec<StateUpdate> {
+        if response.meta.method != Method::Notify {
+            return Vec::new();
+        }
+        let Some(data) = response.data.as_ref() else {
+            return Vec::new();
+        };
+
+        match response.meta.response_type.as_deref() {
+            // Device protection notification
+            Some("protection") => Self::parse_protection_event(data).into_iter().collect(),
+            // Network subscription notification
+            Some("network") => Self::parse_network_updates(data),
+            _ => Vec::new(),
+        }
+    }
+
+    fn parse_protection_event(data: &serde_json::Value) -> Option<StateUpdate> {
+        let device_id = data.get("device").and_then(|v| v.as_str())?;
+        let kind = data.get("kind").and_then(|v| v.as_str())?;
+        Some(StateUpdate::Protection(ProtectionEvent {
+            device_id: device_id.to_string(),
+            kind: ProtectionKind::from_name(kind),
+            active: data.get("active").and_then(|v| v.as_bool()).unwrap_or(true),
+            channel: data.get("channel").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        }))
+    }
+
+    /// Parse every entry of a network notification
+    fn parse_network_updates(data: &serde_json::Value) -> Vec<StateUpdate> {
+        let Some(state_obj) = data.get("state").and_then(|state| state.as_object()) else {
+            return Vec::new();
+        };
+
+        let mut updates = Vec::new();
+        for (state_id, state_entry) in state_obj {
+            let Some(entry_data) = state_entry.get("data") else {
+                continue;
+            };
+
+            match entry_data.get("type").and_then(|v| v.as_str()) {
+                // Raw JSON for room updates
+                Some("room") => updates.push(StateUpdate::RoomUpdate(Arc::new(entry_data.clone()))),
+                Some("device") => {
+                    let device_id = entry_data
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(state_id)
+                        .to_string();
+
+                    match serde_json::from_value::<Device>(entry_data.clone()) {
+                        Ok(device) => updates.push(StateUpdate::DeviceUpdate(device_id, Arc::new(device))),
+                        Err(e) => tracing::debug!("Failed to parse device {}: {}", device_id, e),
                     }
                 }
+                Some(entity_type) => updates.push(StateUpdate::UnknownEntity(
+                    entity_type.to_string(),
+                    Arc::new(entry_data.clone()),
+                )),
+                None => {}
             }
         }
+        updates
+    }
+
+    /// Diff a room update against the last state seen for that room and
+    /// produce the typed change updates it implies
+    fn room_change_updates(state: &ConnectionState, room_json: &serde_json::Value) -> Vec<StateUpdate> {
+        let mut last_seen = state.rooms.lock().unwrap();
+        let parse_context = &state.parse_context;
+        let room_id = room_json
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(|s| uuid::Uuid::parse_str(s).ok());
+        let parsed = match room_id.and_then(|id| last_seen.get(&id)) {
+            Some(old_state) => merge_room_state_from_json(old_state, room_json.clone(), parse_context),
+            None => parse_room_state_from_json(room_json.clone(), parse_context),
+        };
+        let new_state = match parsed {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::debug!("Skipping change detection for unparseable room: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut updates = Vec::new();
+        let room_id = new_state.id;
 
-        None
+        // A room seen for the first time is not a change
+        if let Some(old_state) = last_seen.get(&room_id) {
+            let delta = RoomState::diff(old_state, &new_state);
+            let source = state.change_source(room_id);
+            for field in &delta.changed {
+                let update = match field {
+                    RoomField::Gain => StateUpdate::GainChanged {
+                        room: room_id,
+                        gain: new_state.gain.clone(),
+                        source,
+                    },
+                    RoomField::Mute => StateUpdate::MuteChanged {
+                        room: room_id,
+                        mute: new_state.mute.clone(),
+                        source,
+                    },
+                    RoomField::SelectedInput => StateUpdate::InputChanged {
+                        room: room_id,
+                        input: new_state.selected_input.clone(),
+                        source,
+                    },
+                    RoomField::SelectedVoicingProfile => StateUpdate::VoicingChanged {
+                        room: room_id,
+                        voicing: new_state.selected_voicing_profile.clone(),
+                        source,
+                    },
+                    RoomField::LastSelectedPreset => StateUpdate::PresetApplied {
+                        room: room_id,
+                        preset: new_state.last_selected_preset.clone(),
+                        name: new_state
+                            .last_selected_preset
+                            .as_ref()
+                            .and_then(|id| new_state.presets.get(id))
+                            .map(|preset| preset.name.clone()),
+                        source,
+                    },
+                    RoomField::Sleep => StateUpdate::StandbyChanged {
+                        room: room_id,
+                        sleeping: new_state.sleep,
+                        cause: StandbyCause::infer(source),
+                    },
+                    _ => continue,
+                };
+                updates.push(update);
+            }
+            if !delta.is_empty() {
+                updates.insert(0, StateUpdate::RoomChanged(delta));
+            }
+        }
+
+        last_seen.insert(room_id, new_state);
+        updates
     }
 
     /// Send a request and wait for the response
     pub async fn send_request(&self, request: Request) -> Result<Response> {
+        self.send_request_with_priority(request, Priority::Interactive).await
+    }
+
+    /// Send a request in the given priority lane and wait for the response
+    #[tracing::instrument(
+        name = "request",
+        skip(self, request),
+        fields(
+            endpoint = %request.meta.endpoint,
+            method = %request.meta.method,
+            target = request.meta.target.as_deref(),
+            request_id = %request.meta.id,
+        ),
+        err(Display, level = "debug"),
+    )]
+    pub async fn send_request_with_priority(&self, request: Request, priority: Priority) -> Result<Response> {
+        let rx = self.start_request(&request, priority)?;
+        self.finish_request(&request, rx).await
+    }
+
+    /// Send requests in order, then wait for every response
+    ///
+    /// Every request is written before any response is awaited, so the
+    /// requests cost roughly one round trip together. Requests that are
+    /// already `Err` are not sent and their error is passed through.
+    pub(crate) async fn send_requests(&self, requests: Vec<Result<Request>>) -> Vec<Result<Response>> {
+        let started: Vec<Result<(Request, oneshot::Receiver<Response>)>> = requests
+            .into_iter()
+            .map(|request| {
+                let request = request?;
+                let rx = self.start_request(&request, Priority::Interactive)?;
+                Ok((request, rx))
+            })
+            .collect();
+
+        join_all(started.into_iter().map(|started| async move {
+            let (request, rx) = started?;
+            self.finish_request(&request, rx).await
+        }))
+        .await
+    }
+
+    /// Register a request as pending and write it to the connection
+    fn start_request(&self, request: &Request, priority: Priority) -> Result<oneshot::Receiver<Response>> {
         let request_id = request.id();
         let (tx, rx) = oneshot::channel();
 
-        // Register the pending request
-        {
-            let mut state = self.state.lock().await;
-            state.pending_requests.insert(request_id, tx);
-
-            // Send the request
-            let json = serde_json::to_string(&request)?;
-            tracing::debug!("Sending: {}", json);
+        let json = serde_json::to_string(request)?;
+        tracing::debug!("Sending: {}", json);
 
-            state
-                .ws_tx
-                .send(Message::Text(json))
-                .map_err(|_| AscendError::ConnectionClosed)?;
+        // Register the pending request before sending, so the response can't
+        // arrive unclaimed
+        match self.state.pending_requests.lock().unwrap().entry(request_id) {
+            Entry::Occupied(_) => {
+                return Err(AscendError::InvalidArgument(format!(
+                    "Request ID {} is already in use by a pending request",
+                    request_id
+                )));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(tx);
+            }
+        }
+        self.state.note_command(&request.meta);
+        #[cfg(feature = "journal")]
+        self.state.journal_command(request);
+        if let Err(e) = self.state.send_text(json, priority) {
+            self.state.pending_requests.lock().unwrap().remove(&request_id);
+            return Err(e);
         }
+        Ok(rx)
+    }
 
+    /// Wait for the response to a started request
+    async fn finish_request(&self, request: &Request, rx: oneshot::Receiver<Response>) -> Result<Response> {
         // Wait for response with timeout
-        let response = match timeout(REQUEST_TIMEOUT, rx).await {
+        let response = match timeout(self.options.request_timeout, rx).await {
             Ok(Ok(response)) => response,
             Ok(Err(_)) => return Err(AscendError::ConnectionClosed),
             Err(_) => {
                 // Timeout - remove from pending requests
-                let mut state = self.state.lock().await;
-                state.pending_requests.remove(&request_id);
-                return Err(AscendError::Timeout);
+                self.state.pending_requests.lock().unwrap().remove(&request.id());
+                return Err(AscendError::Timeout {
+                    request: Some(Box::new(RequestContext::from(&request.meta))),
+                });
             }
         };
 
         // Check for API errors
         if response.has_errors() {
             if let Some(detail) = response.error_message() {
-                return Err(AscendError::ApiError { detail });
+                return Err(AscendError::ApiError {
+                    detail,
+                    request: Box::new(RequestContext::from(&request.meta)),
+                });
             }
         }
 
         Ok(response)
     }
 
+    /// Send a request for a typed endpoint and decode the response data
+    pub async fn send_typed<E: TypedEndpoint>(&self, request: Request) -> Result<E::Response> {
+        let response = self.send_request(request).await?;
+        Ok(serde_json::from_value(response.data.unwrap_or(serde_json::Value::Null))?)
+    }
+
     /// Subscribe to state updates
     pub fn subscribe(&self) -> broadcast::Receiver<StateUpdate> {
         self.subscription_tx.subscribe()
     }
 
-    /// Send a request without waiting for a response (fire and forget)
-    pub async fn send_only(&self, request: Request) -> Result<()> {
-        let state = self.state.lock().await;
-        let json = serde_json::to_string(&request)?;
-        tracing::debug!("Sending (no response): {}", json);
+    /// Get the options this connection was established with
+    pub fn options(&self) -> &ConnectOptions {
+        &self.options
+    }
+
+    /// Wait for the room's turn to send a command
+    ///
+    /// Returns `None` without waiting unless `serialize_room_commands` is set.
+    /// Otherwise the returned guard holds the room's queue until dropped;
+    /// waiters are served first come, first served.
+    pub(crate) async fn queue_room_command(&self, room_id: RoomId) -> Option<OwnedMutexGuard<()>> {
+        if !self.options.serialize_room_commands {
+            return None;
+        }
+
+        let queue = self.room_queues.lock().unwrap().entry(room_id).or_default().clone();
+        Some(queue.lock_owned().await)
+    }
+
+    /// Get the parse settings for room documents
+    pub(crate) fn parse_context(&self) -> &ParseContext {
+        &self.parse_context
+    }
 
-        state
-            .ws_tx
-            .send(Message::Text(json))
-            .map_err(|_| AscendError::ConnectionClosed)?;
+    /// Get the unknown room document fields encountered so far
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.parse_context.unknown_fields()
+    }
 
+    /// Start recording every message of this session to a JSONL file
+    ///
+    /// Appends to the file if it exists. Replaces any recording in progress.
+    #[cfg(feature = "recorder")]
+    pub async fn start_recording(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let recorder = SessionRecorder::create(path.as_ref())?;
+        *self.state.recorder.lock().unwrap() = Some(recorder);
         Ok(())
     }
+
+    /// Stop recording, if a recording is in progress
+    #[cfg(feature = "recorder")]
+    pub async fn stop_recording(&self) {
+        *self.state.recorder.lock().unwrap() = None;
+    }
+
+    /// Subscribe to the commands sent on this connection
+    ///
+    /// Reads and subscribe requests are left out.
+    #[cfg(feature = "journal")]
+    pub(crate) fn subscribe_commands(&self) -> broadcast::Receiver<Request> {
+        self.state.commands_tx.subscribe()
+    }
+
+    /// Watch the link status
+    pub fn status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status_tx.subscribe()
+    }
+
+    /// Send a subscribe request and wait for the speaker to accept it
+    ///
+    /// Fails with `AscendError::ApiError` if the speaker rejects the
+    /// subscription, or `AscendError::Timeout` if it doesn't answer within the
+    /// request timeout. The request is remembered and replayed after a reconnect.
+    pub async fn subscribe_request(&self, request: Request) -> Result<()> {
+        self.remember_subscription(&request);
+        self.send_request(request).await.map(|_| ())
+    }
+
+    /// Remember a subscribe request for replaying after a reconnect
+    fn remember_subscription(&self, request: &Request) {
+        if request.meta.method != Method::Subscribe {
+            return;
+        }
+        let mut subscriptions = self.state.subscriptions.lock().unwrap();
+        let known = subscriptions.iter().any(|existing| {
+            existing.meta.endpoint == request.meta.endpoint
+                && existing.meta.target_type == request.meta.target_type
+                && existing.meta.target == request.meta.target
+        });
+        if !known {
+            subscriptions.push(request.clone());
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(io_task) = self.io_task.lock().unwrap().take() {
+            io_task.abort();
+        }
+    }
 }
@@ -0,0 +1,97 @@
+use crate::error::{AscendError, Result};
+use crate::protocol::{Method, Request, TargetType};
+use crate::speaker_connection::SpeakerConnection;
+use crate::types::{Device, DeviceId};
+use std::sync::{Arc, Mutex};
+
+/// Interface for controlling an individual device
+///
+/// A `DeviceHandle` is the device-level counterpart of `Room`: requests are
+/// sent with a device target, for settings that belong to a single speaker
+/// rather than to the room it is part of.
+#[derive(Clone)]
+pub struct DeviceHandle {
+    speaker: Arc<SpeakerConnection>,
+    id: DeviceId,
+    device: Arc<Mutex<Device>>,
+}
+
+impl DeviceHandle {
+    /// Create a new device handle
+    pub(crate) fn new(speaker: Arc<SpeakerConnection>, id: DeviceId, device: Device) -> Self {
+        Self {
+            speaker,
+            id,
+            device: Arc::new(Mutex::new(device)),
+        }
+    }
+
+    /// Get the device ID
+    pub fn id(&self) -> &DeviceId {
+        &self.id
+    }
+
+    /// Get the device name
+    pub fn name(&self) -> String {
+        self.device.lock().unwrap().name.clone()
+    }
+
+    /// Get a snapshot of the device details
+    pub fn device(&self) -> Device {
+        self.device.lock().unwrap().clone()
+    }
+
+    /// Refresh the device details from the speaker
+    pub async fn refresh(&self) -> Result<()> {
+        let mut devices = self.speaker.request_devices().await?;
+        let device = devices
+            .remove(&self.id)
+            .ok_or_else(|| AscendError::DeviceNotFound(self.id.clone()))?;
+
+        *self.device.lock().unwrap() = device;
+        Ok(())
+    }
+
+    /// Read a device-scoped endpoint and return the response data
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let device = client.device("device-id").await?;
+    /// let settings = device.read("settings").await?;
+    /// println!("{}", settings);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read(&self, endpoint: impl Into<String>) -> Result<serde_json::Value> {
+        self.send_raw(endpoint, Method::Read, None).await
+    }
+
+    /// Update a device-scoped endpoint with the given data
+    pub async fn update(&self, endpoint: impl Into<String>, data: serde_json::Value) -> Result<()> {
+        self.send_raw(endpoint, Method::Update, Some(data)).await?;
+        Ok(())
+    }
+
+    /// Send a raw request targeted at this device and return the response data
+    ///
+    /// Returns `Value::Null` if the response carries no data.
+    pub async fn send_raw(
+        &self,
+        endpoint: impl Into<String>,
+        method: Method,
+        data: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let mut request = Request::new(endpoint, method).with_target(TargetType::Device, self.id.clone());
+        if let Some(data) = data {
+            request = request.with_data(data);
+        }
+
+        let response = self.speaker.connection().send_request(request).await?;
+        Ok(response.data.unwrap_or(serde_json::Value::Null))
+    }
+}
@@ -35,7 +35,7 @@ const SPEAKER_PORT: u16 = 8768;
 ///     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 ///
 ///     for room in discovery.rooms() {
-///         println!("Found room: {} at {}", room.name, room.master_ip);
+///         println!("Found room: {} at {}", room.name(), room.speaker_address());
 ///     }
 ///
 ///     discovery.stop().await;
@@ -48,6 +48,8 @@ pub struct Discovery {
     update_tx: Arc<broadcast::Sender<RoomId>>,
     stop_tx: Option<broadcast::Sender<()>>,
     task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Rooms reported by `start` instead of scanning, for `Discovery::fake`
+    fake_rooms: Option<Vec<Room>>,
 }
 
 impl Discovery {
@@ -60,6 +62,44 @@ impl Discovery {
             update_tx: Arc::new(update_tx),
             stop_tx: None,
             task_handle: None,
+            fake_rooms: None,
+        }
+    }
+
+    /// Create a discovery manager that reports the given rooms without any network access
+    ///
+    /// Each call to `start` adds the rooms and sends an update for each one, in
+    /// order, as if a scan had just found them. Combined with `Room::detached`,
+    /// this lets discovery UI flows be tested deterministically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::{Discovery, Room, RoomState};
+    /// use serde_json::json;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let state = RoomState::from_json(json!({
+    ///         "id": "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d",
+    ///         "name": "Studio",
+    ///         "gain": { "global": -20.0 },
+    ///         "mute": { "global": false },
+    ///     }))?;
+    ///
+    ///     let mut discovery = Discovery::fake(vec![Room::detached(state)]);
+    ///     let mut updates = discovery.subscribe_updates();
+    ///     discovery.start().await?;
+    ///
+    ///     let room_id = updates.recv().await?;
+    ///     assert_eq!(discovery.rooms()[0].id(), room_id);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn fake(rooms: Vec<Room>) -> Self {
+        Self {
+            fake_rooms: Some(rooms),
+            ..Self::new()
         }
     }
 
@@ -76,6 +116,15 @@ impl Discovery {
         rooms.values().cloned().collect()
     }
 
+    /// Get the connections to the speakers found so far
+    ///
+    /// Pass them to `RoomManager::add_connection` (or use
+    /// `RoomManager::add_discovered`) to control their rooms through one
+    /// collection.
+    pub fn speakers(&self) -> Vec<Arc<SpeakerConnection>> {
+        self.speakers.lock().unwrap().values().cloned().collect()
+    }
+
     /// Get the number of discovered rooms
     pub fn room_count(&self) -> usize {
         let rooms = self.rooms.lock().unwrap();
@@ -96,6 +145,15 @@ impl Discovery {
         // Stop existing discovery if running
         self.stop().await;
 
+        if let Some(fake_rooms) = &self.fake_rooms {
+            let mut rooms = self.rooms.lock().unwrap();
+            for room in fake_rooms {
+                rooms.insert(room.id(), room.clone());
+                let _ = self.update_tx.send(room.id());
+            }
+            return Ok(());
+        }
+
         let (stop_tx, _) = broadcast::channel(1);
         self.stop_tx = Some(stop_tx.clone());
 
@@ -166,6 +224,7 @@ impl Default for Discovery {
 }
 
 
+#[tracing::instrument(name = "discovery", skip_all)]
 async fn run_discovery_once(
     speakers: &Arc<Mutex<BTreeMap<String, Arc<SpeakerConnection>>>>,
     rooms: &Arc<Mutex<BTreeMap<RoomId, Room>>>,
@@ -279,6 +338,7 @@ fn parse_speaker_ips(data: &serde_json::Value) -> Option<Vec<String>> {
 }
 
 /// Process a single speaker: connect, get network state, subscribe, and add rooms
+#[tracing::instrument(skip(speakers, rooms, update_tx))]
 async fn process_speaker(
     speaker_ip: &str,
     speakers: &Arc<Mutex<BTreeMap<String, Arc<SpeakerConnection>>>>,
@@ -386,7 +446,7 @@ async fn process_state_update(
             let mut rooms_lock = rooms.lock().unwrap();
             if let Some(room) = rooms_lock.get(&room_id) {
                 // Update existing room
-                if let Err(e) = room.update_from_json(*room_json) {
+                if let Err(e) = room.update_from_json(Arc::unwrap_or_clone(room_json)) {
                     tracing::warn!("Failed to update room {}: {}", room_id, e);
                 } else {
                     let _ = update_tx.send(room_id);
@@ -394,7 +454,7 @@ async fn process_state_update(
             } else {
                 // New room discovered via update
                 tracing::info!("New room discovered via update: {}", room_id);
-                match Room::new(speaker.clone(), *room_json) {
+                match Room::new(speaker.clone(), Arc::unwrap_or_clone(room_json)) {
                     Ok(new_room) => {
                         rooms_lock.insert(room_id, new_room);
                         let _ = update_tx.send(room_id);
@@ -1,28 +1,43 @@
+use crate::protocol::{Method, RequestMeta, TargetType};
+use crate::types::GainDb;
+use std::fmt;
 use thiserror::Error;
+use uuid::Uuid;
 
 /// Result type for Ascend operations
 pub type Result<T> = std::result::Result<T, AscendError>;
 
 /// Errors that can occur when interacting with Ascend speakers
+///
+/// New variants may be added in minor releases; use the classification helpers
+/// such as [`is_retryable`](Self::is_retryable) rather than matching exhaustively.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum AscendError {
     /// WebSocket connection error
+    ///
+    /// Boxed to keep `Result` small, as the WebSocket error is large.
     #[error("WebSocket error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
 
     /// Connection was closed unexpectedly
     #[error("Connection closed")]
     ConnectionClosed,
 
     /// Request timed out waiting for response
-    #[error("Request timeout")]
-    Timeout,
+    #[error("Request timeout{}", .request.as_ref().map(|r| format!(" ({})", r)).unwrap_or_default())]
+    Timeout {
+        /// The request that went unanswered, if the timeout applied to one
+        request: Option<Box<RequestContext>>,
+    },
 
     /// API returned an error response
-    #[error("API error: {detail}")]
+    #[error("API error: {detail} ({request})")]
     ApiError {
         /// Error detail message from the API
         detail: String,
+        /// The request the speaker rejected
+        request: Box<RequestContext>,
     },
 
     /// JSON serialization/deserialization error
@@ -37,11 +52,152 @@ pub enum AscendError {
     #[error("Room not found: {0}")]
     RoomNotFound(String),
 
+    /// A single room was requested but several exist
+    #[error("Multiple rooms found: {0}")]
+    AmbiguousRoom(String),
+
+    /// Device not found in network state
+    #[error("Device not found: {0}")]
+    DeviceNotFound(String),
+
     /// Invalid or unexpected response from API
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
+    /// Invalid argument supplied by the caller
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// The speaker's firmware does not support the requested feature
+    #[error("Unsupported by speaker firmware: {0}")]
+    Unsupported(String),
+
+    /// A gain above the room's safety limit was requested
+    #[error("Gain {requested} dB exceeds the safety limit of {limit} dB")]
+    SafetyLimit {
+        /// The gain that was requested
+        requested: GainDb,
+        /// The room's safety limit
+        limit: GainDb,
+    },
+
+    /// A gain rise was faster than the room's slew limit allows
+    #[error("Gain rise too fast: requested {requested} dB, at most {allowed} dB allowed now")]
+    SlewRateExceeded {
+        /// The gain that was requested
+        requested: GainDb,
+        /// The highest gain the slew limit allowed at the time
+        allowed: GainDb,
+    },
+
     /// Channel receive error
     #[error("Channel error: {0}")]
     ChannelError(String),
+
+    /// Trace export could not be set up
+    #[error("Telemetry error: {0}")]
+    Telemetry(String),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for AscendError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(error))
+    }
+}
+
+impl AscendError {
+    /// Whether the same request may succeed if sent again
+    ///
+    /// True for transport failures and timeouts. Errors reported by the speaker
+    /// or caused by the caller's input will fail the same way on retry.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// if let Err(e) = room.set_gain(-20.0).await {
+    ///     if e.is_retryable() {
+    ///         client.reconnect().await?;
+    ///         room.set_gain(-20.0).await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::WebSocket(_) | Self::ConnectionClosed | Self::Timeout { .. } | Self::Io(_)
+        )
+    }
+
+    /// Whether the request timed out waiting for a response
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// Get the ID of the request the error occurred on, if it is known
+    ///
+    /// Matches the ID generated by `Request::new` or set with `Request::with_id`.
+    pub fn request_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Timeout { request } => request.as_ref().map(|request| request.request_id),
+            Self::ApiError { request, .. } => Some(request.request_id),
+            _ => None,
+        }
+    }
+
+    /// Whether the speaker rejected the request or sent a message that could not be understood
+    pub fn is_protocol(&self) -> bool {
+        matches!(self, Self::ApiError { .. } | Self::InvalidResponse(_) | Self::Json(_))
+    }
+}
+
+/// Identifies the request an error occurred on
+///
+/// Displayed as e.g. `update gain2 on room 5f0c… (request 9b1e…)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// Endpoint name
+    pub endpoint: String,
+
+    /// Method the endpoint was invoked with
+    pub method: Method,
+
+    /// Kind of target, if the request was targeted
+    pub target_type: Option<TargetType>,
+
+    /// Room or device the request was targeted at
+    pub target: Option<String>,
+
+    /// Request ID, matching the `id` in the protocol message
+    pub request_id: Uuid,
+}
+
+impl From<&RequestMeta> for RequestContext {
+    fn from(meta: &RequestMeta) -> Self {
+        Self {
+            endpoint: meta.endpoint.clone(),
+            method: meta.method,
+            target_type: meta.target_type,
+            target: meta.target.clone(),
+            request_id: meta.id,
+        }
+    }
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.endpoint)?;
+        match (&self.target_type, &self.target) {
+            (Some(target_type), Some(target)) => write!(f, " on {} {}", target_type, target)?,
+            (None, Some(target)) => write!(f, " on {}", target)?,
+            _ => {}
+        }
+        write!(f, " (request {})", self.request_id)
+    }
 }
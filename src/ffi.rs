@@ -0,0 +1,413 @@
+//! C ABI for embedding the client in C and C++ control software (requires the `ffi` feature)
+//!
+//! The matching header is committed as `include/dutchdutch_ascend.h`. Builds
+//! with the `ffi` feature regenerate it into the build's `OUT_DIR`; after
+//! changing this module, refresh the committed copy with
+//! `cbindgen --config cbindgen.toml --output include/dutchdutch_ascend.h`.
+//! Build a linkable library with e.g.
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! All functions block the calling thread until the operation completes; they
+//! are driven by a runtime owned by the library. Functions return an
+//! [`AscendStatus`]; on failure, [`ascend_last_error`] describes the error.
+//!
+//! State callbacks run on the library's runtime threads, which must not block.
+//! Calls that talk to the speaker (connecting, looking up a room, and the
+//! setters) fail with [`AscendStatus::InCallback`] when made from inside a
+//! callback; hand the work to another thread instead.
+//!
+//! ```c
+//! AscendClientHandle *client;
+//! AscendRoomHandle *room;
+//! if (ascend_client_connect("192.168.1.100", &client) != ASCEND_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", ascend_last_error());
+//!     return 1;
+//! }
+//! ascend_client_room(client, NULL, &room);
+//! ascend_room_set_gain(room, -25.0);
+//! ascend_room_free(room);
+//! ascend_client_free(client);
+//! ```
+
+use crate::client::AscendClient;
+use crate::error::AscendError;
+use crate::room::{Room, RoomState};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::future::Future;
+use std::ptr;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+/// Result of an FFI call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AscendStatus {
+    Ok = 0,
+    /// A pointer was null or a string was not valid UTF-8
+    InvalidArgument = 1,
+    /// The connection could not be established or was lost
+    ConnectionFailed = 2,
+    /// The speaker did not answer in time
+    Timeout = 3,
+    /// The speaker rejected the request
+    ApiError = 4,
+    /// The requested room does not exist, or several match
+    RoomNotFound = 5,
+    /// The speaker's firmware does not support the request
+    Unsupported = 6,
+    /// Any other error
+    Other = 7,
+    /// A blocking function was called from inside a state callback
+    InCallback = 8,
+}
+
+/// Opaque handle to a connected client
+pub struct AscendClientHandle {
+    client: AscendClient,
+}
+
+/// Opaque handle to a room
+pub struct AscendRoomHandle {
+    room: Room,
+    /// Task delivering state changes to the registered callback
+    callback_task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Drop for AscendRoomHandle {
+    fn drop(&mut self) {
+        if let Some(task) = self.callback_task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Room state passed to an [`AscendStateCallback`]
+///
+/// The strings are only valid for the duration of the callback.
+#[repr(C)]
+pub struct AscendRoomState {
+    /// Global gain in dB
+    pub gain: f64,
+    pub mute: bool,
+    pub standby: bool,
+    /// Selected input, or null if none is reported
+    pub input: *const c_char,
+    /// The complete room document as JSON
+    pub json: *const c_char,
+}
+
+/// Called with the new room state after every change
+///
+/// Invoked on a library-owned runtime thread, never concurrently for the same
+/// room. The callback must not block, and blocking library calls made from it
+/// fail with `InCallback`.
+pub type AscendStateCallback = Option<extern "C" fn(state: *const AscendRoomState, user_data: *mut c_void)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("failed to start the FFI runtime"))
+}
+
+/// Record an error for `ascend_last_error` and return its status
+fn fail(error: AscendError) -> AscendStatus {
+    let status = match &error {
+        AscendError::InvalidArgument(_) => AscendStatus::InvalidArgument,
+        AscendError::WebSocket(_) | AscendError::ConnectionClosed | AscendError::Io(_) => {
+            AscendStatus::ConnectionFailed
+        }
+        AscendError::Timeout { .. } => AscendStatus::Timeout,
+        AscendError::ApiError { .. } => AscendStatus::ApiError,
+        AscendError::RoomNotFound(_) | AscendError::AmbiguousRoom(_) => AscendStatus::RoomNotFound,
+        AscendError::Unsupported(_) => AscendStatus::Unsupported,
+        _ => AscendStatus::Other,
+    };
+    let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// Run an async operation to completion, recording any error
+///
+/// Refuses to run on a runtime thread, i.e. from a state callback, where
+/// blocking would panic across the C boundary.
+fn run<T>(operation: impl Future<Output = crate::error::Result<T>>) -> Result<T, AscendStatus> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        let message = c"blocking calls are not allowed from a state callback";
+        LAST_ERROR.with(|last| *last.borrow_mut() = Some(message.to_owned()));
+        return Err(AscendStatus::InCallback);
+    }
+    runtime().block_on(operation).map_err(fail)
+}
+
+/// Run an async operation without a result to completion
+fn block_on(operation: impl Future<Output = crate::error::Result<()>>) -> AscendStatus {
+    match run(operation) {
+        Ok(()) => AscendStatus::Ok,
+        Err(status) => status,
+    }
+}
+
+/// Read a C string argument, rejecting null and invalid UTF-8
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn string_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, AscendStatus> {
+    if s.is_null() {
+        return Err(fail(AscendError::InvalidArgument(format!("{} is null", name))));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| fail(AscendError::InvalidArgument(format!("{} is not valid UTF-8", name))))
+}
+
+fn null_argument(name: &str) -> AscendStatus {
+    fail(AscendError::InvalidArgument(format!("{} is null", name)))
+}
+
+/// Describe the last error on the calling thread
+///
+/// Returns null if no call on this thread has failed yet. The string stays
+/// valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn ascend_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Connect to a speaker
+///
+/// `address` accepts `host`, `host:port`, or `ws://host:port`. On success,
+/// `*out` receives a handle to release with `ascend_client_free`.
+///
+/// # Safety
+///
+/// `address` must be a NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_client_connect(
+    address: *const c_char,
+    out: *mut *mut AscendClientHandle,
+) -> AscendStatus {
+    let address = match string_arg(address, "address") {
+        Ok(address) => address,
+        Err(status) => return status,
+    };
+    if out.is_null() {
+        return null_argument("out");
+    }
+
+    match run(AscendClient::connect_url(address)) {
+        Ok(client) => {
+            *out = Box::into_raw(Box::new(AscendClientHandle { client }));
+            AscendStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Disconnect and release a client
+///
+/// Room handles obtained from the client stay valid and their requests keep
+/// working, since each holds its own reference to the speaker connection; it
+/// is closed once the client and all of its rooms are freed. Changes reported
+/// by the speaker are no longer applied to those rooms, so their state, and
+/// their state callbacks, only follow their own setters.
+///
+/// # Safety
+///
+/// `client` must be null or a handle from `ascend_client_connect` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_client_free(client: *mut AscendClientHandle) {
+    if !client.is_null() {
+        let client = Box::from_raw(client);
+        // The client's background tasks are aborted inside the runtime
+        let _guard = runtime().enter();
+        drop(client);
+    }
+}
+
+/// Get a room by name, or the only room if `name` is null
+///
+/// On success, `*out` receives a handle to release with `ascend_room_free`.
+///
+/// # Safety
+///
+/// `client` must be a valid client handle, `name` null or a NUL-terminated
+/// string, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_client_room(
+    client: *const AscendClientHandle,
+    name: *const c_char,
+    out: *mut *mut AscendRoomHandle,
+) -> AscendStatus {
+    let Some(client) = client.as_ref() else {
+        return null_argument("client");
+    };
+    if out.is_null() {
+        return null_argument("out");
+    }
+    let name = if name.is_null() {
+        None
+    } else {
+        match string_arg(name, "name") {
+            Ok(name) => Some(name),
+            Err(status) => return status,
+        }
+    };
+
+    let room = run(async {
+        match name {
+            Some(name) => client.client.room_by_name(name).await,
+            None => client.client.room().await,
+        }
+    });
+    match room {
+        Ok(room) => {
+            *out = Box::into_raw(Box::new(AscendRoomHandle {
+                room,
+                callback_task: None,
+            }));
+            AscendStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Release a room handle, unregistering its state callback
+///
+/// # Safety
+///
+/// `room` must be null or a handle from `ascend_client_room` that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_free(room: *mut AscendRoomHandle) {
+    if !room.is_null() {
+        drop(Box::from_raw(room));
+    }
+}
+
+/// Get the room's global gain in dB
+///
+/// # Safety
+///
+/// `room` must be a valid room handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_gain(room: *const AscendRoomHandle, out: *mut f64) -> AscendStatus {
+    let Some(room) = room.as_ref() else {
+        return null_argument("room");
+    };
+    if out.is_null() {
+        return null_argument("out");
+    }
+    *out = room.room.gain().global.db();
+    AscendStatus::Ok
+}
+
+/// Set the room's global gain in dB
+///
+/// # Safety
+///
+/// `room` must be a valid room handle.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_set_gain(room: *const AscendRoomHandle, gain: f64) -> AscendStatus {
+    let Some(room) = room.as_ref() else {
+        return null_argument("room");
+    };
+    block_on(room.room.set_gain(gain))
+}
+
+/// Get the room's global mute state
+///
+/// # Safety
+///
+/// `room` must be a valid room handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_mute(room: *const AscendRoomHandle, out: *mut bool) -> AscendStatus {
+    let Some(room) = room.as_ref() else {
+        return null_argument("room");
+    };
+    if out.is_null() {
+        return null_argument("out");
+    }
+    *out = room.room.mute().global;
+    AscendStatus::Ok
+}
+
+/// Set the room's global mute state
+///
+/// # Safety
+///
+/// `room` must be a valid room handle.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_set_mute(room: *const AscendRoomHandle, mute: bool) -> AscendStatus {
+    let Some(room) = room.as_ref() else {
+        return null_argument("room");
+    };
+    block_on(room.room.set_mute(mute))
+}
+
+/// User data pointer handed back to the callback
+struct UserData(*mut c_void);
+
+// The pointer is only passed back to the caller's callback, which is
+// responsible for any synchronization
+unsafe impl Send for UserData {}
+
+/// Register a callback invoked after every change to the room's state
+///
+/// Replaces any previously registered callback. Pass a null callback to
+/// unregister.
+///
+/// # Safety
+///
+/// `room` must be a valid room handle. `user_data` is passed back unchanged
+/// and must stay valid until the callback is replaced or the room is freed.
+#[no_mangle]
+pub unsafe extern "C" fn ascend_room_set_state_callback(
+    room: *mut AscendRoomHandle,
+    callback: AscendStateCallback,
+    user_data: *mut c_void,
+) -> AscendStatus {
+    let Some(room) = room.as_mut() else {
+        return null_argument("room");
+    };
+    if let Some(task) = room.callback_task.take() {
+        task.abort();
+    }
+    let Some(callback) = callback else {
+        return AscendStatus::Ok;
+    };
+
+    let mut states = room.room.watch();
+    let user_data = UserData(user_data);
+    room.callback_task = Some(runtime().spawn(async move {
+        let user_data = user_data;
+        while states.changed().await.is_ok() {
+            let state = states.borrow_and_update().clone();
+            invoke(callback, &state, user_data.0);
+        }
+    }));
+    AscendStatus::Ok
+}
+
+fn invoke(callback: extern "C" fn(*const AscendRoomState, *mut c_void), state: &RoomState, user_data: *mut c_void) {
+    let input = state
+        .selected_input
+        .as_deref()
+        .and_then(|input| CString::new(input).ok());
+    let json = CString::new(state.raw_json.to_string()).unwrap_or_default();
+
+    let state = AscendRoomState {
+        gain: state.gain.global.db(),
+        mute: state.mute.global,
+        standby: state.sleep,
+        input: input.as_ref().map_or(ptr::null(), |input| input.as_ptr()),
+        json: json.as_ptr(),
+    };
+    callback(&state, user_data);
+}
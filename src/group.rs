@@ -0,0 +1,155 @@
+use crate::error::Result;
+use crate::room::Room;
+use crate::types::{GainDb, MuteState, RoomId};
+use futures_util::future::join_all;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Several rooms linked for volume control
+///
+/// The gain offsets between rooms are captured when the group is created, and
+/// `set_gain` moves every room together so those offsets are preserved. The
+/// group gain starts out as the first room's gain, and each room is set to the
+/// group gain plus its offset.
+///
+/// A room's gain is clamped to its own limits and safety limit. The group
+/// keeps its gain unclamped, so once the group moves back into range, every
+/// room is at its captured offset again.
+///
+/// Commands fan out concurrently and report a result per room, so a failure in
+/// one room does not prevent the others from being updated.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dutchdutch_ascend::{AscendClient, RoomGroup};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+/// let group = RoomGroup::new(client.rooms().await?);
+/// for (room_id, result) in group.set_gain(-25.0).await {
+///     match result {
+///         Ok(member) if member.clamped => println!("Room {} stopped at {} dB", room_id, member.gain),
+///         Ok(_) => {}
+///         Err(e) => eprintln!("Room {} failed: {}", room_id, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct RoomGroup {
+    /// Rooms with their gain offset relative to the group gain
+    members: Vec<(Room, GainDb)>,
+    /// Gain the group was last set to, before any room's clamping
+    gain: Mutex<Option<GainDb>>,
+}
+
+impl Clone for RoomGroup {
+    fn clone(&self) -> Self {
+        Self {
+            members: self.members.clone(),
+            gain: Mutex::new(self.gain()),
+        }
+    }
+}
+
+/// Gain a room of a `RoomGroup` was set to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemberGain {
+    /// Gain sent to the room
+    pub gain: GainDb,
+
+    /// Whether the room's limits kept it from the group gain plus its offset
+    pub clamped: bool,
+}
+
+impl RoomGroup {
+    /// Link rooms into a group, capturing their current relative gain offsets
+    pub fn new(rooms: impl IntoIterator<Item = Room>) -> Self {
+        let mut group = Self {
+            members: rooms.into_iter().map(|room| (room, GainDb::default())).collect(),
+            gain: Mutex::new(None),
+        };
+        group.relink();
+        group
+    }
+
+    /// Recapture the relative gain offsets from the rooms' current state
+    ///
+    /// The group gain is reset to the first room's gain.
+    pub fn relink(&mut self) {
+        let reference = self.members.first().map(|(room, _)| room.gain().global);
+        *self.gain.lock().unwrap() = reference;
+        let Some(reference) = reference else {
+            return;
+        };
+        for (room, offset) in &mut self.members {
+            *offset = room.gain().global - reference;
+        }
+    }
+
+    /// Get the rooms in the group
+    pub fn rooms(&self) -> Vec<Room> {
+        self.members.iter().map(|(room, _)| room.clone()).collect()
+    }
+
+    /// Get the gain offset of each room relative to the group gain
+    pub fn offsets(&self) -> BTreeMap<RoomId, GainDb> {
+        self.members.iter().map(|(room, offset)| (room.id(), *offset)).collect()
+    }
+
+    /// Get the group gain
+    ///
+    /// This is the gain last passed to `set_gain`, or the first room's gain
+    /// when the group was linked. It may lie outside a room's limits.
+    pub fn gain(&self) -> Option<GainDb> {
+        *self.gain.lock().unwrap()
+    }
+
+    /// Set the group gain, moving every room while preserving offsets
+    ///
+    /// Each room's target is clamped to its own gain limits and safety limit;
+    /// the result says where each room ended up and whether it was clamped.
+    pub async fn set_gain(&self, gain: impl Into<GainDb>) -> BTreeMap<RoomId, Result<MemberGain>> {
+        let gain = gain.into();
+        if !self.members.is_empty() {
+            *self.gain.lock().unwrap() = Some(gain);
+        }
+
+        let results = join_all(self.members.iter().map(|(room, offset)| async move {
+            let wanted = gain + *offset;
+            let mut target = wanted.clamp_to(&room.gain().limits);
+            if let Some(limit) = room.safety_limit().filter(|limit| target > *limit) {
+                target = limit;
+            }
+            let result = room.set_gain(target).await.map(|()| MemberGain {
+                gain: target,
+                clamped: target != wanted,
+            });
+            (room.id(), result)
+        }))
+        .await;
+
+        results.into_iter().collect()
+    }
+
+    /// Adjust the group gain by a relative amount in dB
+    pub async fn adjust_gain(&self, delta: impl Into<GainDb>) -> BTreeMap<RoomId, Result<MemberGain>> {
+        match self.gain() {
+            Some(gain) => self.set_gain(gain + delta.into()).await,
+            None => BTreeMap::new(),
+        }
+    }
+
+    /// Set the global mute state of every room
+    pub async fn set_mute(&self, mute: MuteState) -> BTreeMap<RoomId, Result<()>> {
+        let results = join_all(
+            self.members
+                .iter()
+                .map(|(room, _)| async move { (room.id(), room.set_mute(mute).await) }),
+        )
+        .await;
+
+        results.into_iter().collect()
+    }
+}
@@ -0,0 +1,273 @@
+//! Home Assistant MQTT discovery payloads (requires the `homeassistant` feature)
+//!
+//! [`discovery_messages`] builds the retained config messages that make a room
+//! appear in Home Assistant with a volume slider, mute and standby switches,
+//! and input and preset selects. The crate does not speak MQTT itself: the
+//! caller publishes the messages with its client of choice, publishes
+//! [`state_payload`] to the room's state topic whenever the room changes, and
+//! maps messages on the command topics back to [`Room`](crate::Room) setters.
+//!
+//! With a state prefix of `ascend` and a command prefix of `ascend/set`, the
+//! topics of a room are:
+//!
+//! | Topic | Payload |
+//! |-------|---------|
+//! | `ascend/availability` | `online` or `offline`, published by the caller |
+//! | `ascend/<room id>` | [`state_payload`] of the room |
+//! | `ascend/set/<room id>/volume` | gain in dB, e.g. `-25.5` |
+//! | `ascend/set/<room id>/mute` | `ON` or `OFF` |
+//! | `ascend/set/<room id>/standby` | `ON` or `OFF` |
+//! | `ascend/set/<room id>/input` | input name, e.g. `XLR` |
+//! | `ascend/set/<room id>/preset` | preset name, mapped to an ID with [`preset_id`] |
+//!
+//! Every entity is one the stock MQTT integration supports: a `number` for
+//! the volume, `switch`es for mute and standby, and `select`s for the input
+//! and preset.
+//!
+//! ```no_run
+//! use dutchdutch_ascend::homeassistant::{discovery_messages, DiscoveryTopics};
+//! use dutchdutch_ascend::AscendClient;
+//!
+//! # async fn example() -> dutchdutch_ascend::Result<()> {
+//! let client = AscendClient::connect("192.168.1.100", 8768).await?;
+//! let topics = DiscoveryTopics::new("ascend", "ascend/set");
+//! for room in client.rooms().await? {
+//!     for message in discovery_messages(&room.state_snapshot(), &topics) {
+//!         // Publish retained with your MQTT client
+//!         println!("{} {}", message.topic, message.payload);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::room::RoomState;
+use serde_json::{json, Value};
+
+/// Topic prefixes of the discovery, state, and command messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryTopics {
+    /// Prefix Home Assistant listens on for config messages, `homeassistant` by default
+    pub discovery_prefix: String,
+
+    /// Prefix of the per-room state topics
+    pub state_prefix: String,
+
+    /// Prefix of the per-room command topics
+    pub command_prefix: String,
+}
+
+impl DiscoveryTopics {
+    /// Use the given state and command prefixes and the default discovery prefix
+    pub fn new(state_prefix: impl Into<String>, command_prefix: impl Into<String>) -> Self {
+        Self {
+            discovery_prefix: "homeassistant".to_string(),
+            state_prefix: trim(state_prefix.into()),
+            command_prefix: trim(command_prefix.into()),
+        }
+    }
+
+    /// Publish config messages under `prefix` instead of `homeassistant`
+    pub fn with_discovery_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.discovery_prefix = trim(prefix.into());
+        self
+    }
+
+    /// Topic the room's [`state_payload`] is published to
+    pub fn state_topic(&self, state: &RoomState) -> String {
+        format!("{}/{}", self.state_prefix, state.id)
+    }
+
+    /// Topic the caller publishes `online` and `offline` to as the speaker connects and disconnects
+    pub fn availability_topic(&self) -> String {
+        format!("{}/availability", self.state_prefix)
+    }
+
+    /// Topic a command for the room is received on, e.g. `volume`
+    pub fn command_topic(&self, state: &RoomState, command: &str) -> String {
+        format!("{}/{}/{}", self.command_prefix, state.id, command)
+    }
+}
+
+fn trim(prefix: String) -> String {
+    prefix.trim_end_matches('/').to_string()
+}
+
+/// Retained config message announcing one entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryMessage {
+    pub topic: String,
+    pub payload: Value,
+}
+
+/// Config messages for the entities of a room
+///
+/// Returns a volume number, mute and standby switches, and an input select,
+/// plus a preset select if the room has presets. Publish them retained, and
+/// again when inputs or presets change so the options stay current.
+pub fn discovery_messages(state: &RoomState, topics: &DiscoveryTopics) -> Vec<DiscoveryMessage> {
+    let state_topic = topics.state_topic(state);
+    let inputs: Vec<&str> = state.input_modes.iter().map(|input| input.as_str()).collect();
+    let limits = &state.gain.limits;
+    let step = if limits.step > 0.0 { limits.step } else { 0.5 };
+
+    let mut messages = vec![
+        entity(
+            topics,
+            state,
+            "number",
+            "volume",
+            json!({
+                "name": "Volume",
+                "icon": "mdi:volume-high",
+                "state_topic": state_topic,
+                "value_template": "{{ value_json.volume }}",
+                "command_topic": topics.command_topic(state, "volume"),
+                "min": limits.min,
+                "max": limits.max,
+                "step": step,
+                "mode": "slider",
+                "unit_of_measurement": "dB",
+            }),
+        ),
+        entity(
+            topics,
+            state,
+            "switch",
+            "mute",
+            json!({
+                "name": "Mute",
+                "icon": "mdi:volume-off",
+                "state_topic": state_topic,
+                "value_template": "{{ value_json.mute }}",
+                "command_topic": topics.command_topic(state, "mute"),
+            }),
+        ),
+        entity(
+            topics,
+            state,
+            "switch",
+            "standby",
+            json!({
+                "name": "Standby",
+                "icon": "mdi:power-sleep",
+                "state_topic": state_topic,
+                "value_template": "{{ value_json.standby }}",
+                "command_topic": topics.command_topic(state, "standby"),
+            }),
+        ),
+        entity(
+            topics,
+            state,
+            "select",
+            "input",
+            json!({
+                "name": "Input",
+                "icon": "mdi:import",
+                "state_topic": state_topic,
+                "value_template": "{{ value_json.input }}",
+                "command_topic": topics.command_topic(state, "input"),
+                "options": inputs,
+            }),
+        ),
+    ];
+
+    if !state.presets.is_empty() {
+        let presets: Vec<&str> = state.presets.values().map(|preset| preset.name.as_str()).collect();
+        messages.push(entity(
+            topics,
+            state,
+            "select",
+            "preset",
+            json!({
+                "name": "Preset",
+                "icon": "mdi:playlist-music",
+                "state_topic": state_topic,
+                "value_template": "{{ value_json.preset }}",
+                "command_topic": topics.command_topic(state, "preset"),
+                "options": presets,
+            }),
+        ));
+    }
+
+    messages
+}
+
+/// State document read by the entities of [`discovery_messages`]
+///
+/// Publish it retained to [`DiscoveryTopics::state_topic`] whenever the room
+/// changes. Switches are `ON`/`OFF`, and the preset is given by name; the
+/// input and preset are `null` when none is selected.
+pub fn state_payload(state: &RoomState) -> Value {
+    let preset = state
+        .last_selected_preset
+        .as_ref()
+        .and_then(|id| state.presets.get(id))
+        .map(|preset| preset.name.as_str());
+    json!({
+        "volume": state.gain.global.db(),
+        "mute": on_off(state.mute.global),
+        "standby": on_off(state.sleep),
+        "input": state.selected_input,
+        "preset": preset,
+    })
+}
+
+/// ID of the room's preset named `name`, for handling the `preset` command
+///
+/// The preset select lists presets by name; pass the ID to
+/// [`Room::select_preset`](crate::Room::select_preset). Returns the first match
+/// if several presets share the name.
+pub fn preset_id<'a>(state: &'a RoomState, name: &str) -> Option<&'a str> {
+    state
+        .presets
+        .iter()
+        .find(|(_, preset)| preset.name == name)
+        .map(|(id, _)| id.as_str())
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+/// Config message of one entity, with the fields shared by every entity added
+fn entity(
+    topics: &DiscoveryTopics,
+    state: &RoomState,
+    component: &str,
+    object: &str,
+    mut config: Value,
+) -> DiscoveryMessage {
+    let node = format!("ascend_{}", state.id.simple());
+    config["unique_id"] = json!(format!("{}_{}", node, object));
+    config["object_id"] = json!(format!("{}_{}", slug(&state.name), object));
+    config["availability_topic"] = json!(topics.availability_topic());
+    config["device"] = json!({
+        "identifiers": [node],
+        "name": state.name,
+        "manufacturer": "Dutch & Dutch",
+        "model": "Ascend",
+    });
+    DiscoveryMessage {
+        topic: format!("{}/{}/{}/{}/config", topics.discovery_prefix, component, node, object),
+        payload: config,
+    }
+}
+
+/// Lowercase the room name into an entity ID, e.g. `living_room`
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let slug = slug.split('_').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("_");
+    if slug.is_empty() {
+        "ascend".to_string()
+    } else {
+        slug
+    }
+}
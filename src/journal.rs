@@ -0,0 +1,270 @@
+//! State change journal (requires the `journal` feature)
+//!
+//! A [`Journal`] appends one [`JournalEntry`] per line to any `AsyncWrite`:
+//! every room state change reported by the speaker and every command sent by
+//! the client. Unlike a session recording, which captures raw protocol
+//! messages for bug reports, a journal is meant to be kept for a long time and
+//! analyzed offline, e.g. to see when and how loud each room is played.
+
+use crate::client::AscendClient;
+use crate::error::{AscendError, Result};
+use crate::protocol::{Method, Request, TargetType};
+use crate::room::RoomField;
+use crate::subscription::{ChangeSource, StandbyCause, StateUpdate};
+use crate::types::{GainDb, RoomId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::broadcast;
+
+/// A single line of a journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// What a journal line records
+///
+/// Serialized with an `event` tag, e.g.
+/// `{"timestampMs":1718000000000,"event":"gain","room":"…","gain":-24.0,"source":"external"}`.
+/// A `roomChanged` entry lists every field that changed; the gain, mute,
+/// input, voicing, preset, and standby entries that follow carry the new values.
+/// Standby entries carry a `cause` instead of a `source`, since a room can also
+/// go to sleep on its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum JournalEvent {
+    /// A command was sent to the speaker
+    Command {
+        endpoint: String,
+        method: Method,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target_type: Option<TargetType>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<Value>,
+    },
+    RoomChanged {
+        room: RoomId,
+        fields: Vec<RoomField>,
+    },
+    Gain {
+        room: RoomId,
+        gain: GainDb,
+        source: ChangeSource,
+    },
+    Mute {
+        room: RoomId,
+        muted: bool,
+        source: ChangeSource,
+    },
+    Input {
+        room: RoomId,
+        input: Option<String>,
+        source: ChangeSource,
+    },
+    Voicing {
+        room: RoomId,
+        voicing: Option<String>,
+        source: ChangeSource,
+    },
+    Preset {
+        room: RoomId,
+        preset: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        source: ChangeSource,
+    },
+    Standby {
+        room: RoomId,
+        sleeping: bool,
+        cause: StandbyCause,
+    },
+}
+
+impl JournalEvent {
+    /// Journal event for a state update, if it is a room state change
+    fn from_update(update: StateUpdate) -> Option<Self> {
+        let event = match update {
+            StateUpdate::RoomChanged(delta) => Self::RoomChanged {
+                room: delta.room_id,
+                fields: delta.changed,
+            },
+            StateUpdate::GainChanged { room, gain, source } => Self::Gain {
+                room,
+                gain: gain.global,
+                source,
+            },
+            StateUpdate::MuteChanged { room, mute, source } => Self::Mute {
+                room,
+                muted: mute.global,
+                source,
+            },
+            StateUpdate::InputChanged { room, input, source } => Self::Input { room, input, source },
+            StateUpdate::VoicingChanged { room, voicing, source } => Self::Voicing { room, voicing, source },
+            StateUpdate::PresetApplied {
+                room,
+                preset,
+                name,
+                source,
+            } => Self::Preset {
+                room,
+                preset,
+                name,
+                source,
+            },
+            StateUpdate::StandbyChanged { room, sleeping, cause } => Self::Standby { room, sleeping, cause },
+            _ => return None,
+        };
+        Some(event)
+    }
+
+    fn from_command(request: Request) -> Self {
+        Self::Command {
+            endpoint: request.meta.endpoint,
+            method: request.meta.method,
+            target_type: request.meta.target_type,
+            target: request.meta.target,
+            data: request.data,
+        }
+    }
+}
+
+/// Appends room state changes and issued commands of one client to a writer
+///
+/// Each line is flushed as it is written. Write failures are logged and the
+/// journal keeps going with the next entry.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::journal::Journal;
+/// use dutchdutch_ascend::AscendClient;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///
+///     let file = tokio::fs::OpenOptions::new()
+///         .create(true)
+///         .append(true)
+///         .open("living-room.jsonl")
+///         .await?;
+///     let mut journal = Journal::new(file);
+///     journal.start(&client).await?;
+///
+///     tokio::signal::ctrl_c().await?;
+///     journal.stop();
+///     Ok(())
+/// }
+/// ```
+pub struct Journal {
+    writer: Option<Box<dyn AsyncWrite + Unpin + Send>>,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Journal {
+    /// Create a journal writing to `writer`
+    pub fn new(writer: impl AsyncWrite + Unpin + Send + 'static) -> Self {
+        Self {
+            writer: Some(Box::new(writer)),
+            task_handle: None,
+        }
+    }
+
+    /// Start journaling the client's changes and commands in the background
+    ///
+    /// The writer is handed to the background task, so a journal can only be
+    /// started once; later calls fail with `AscendError::InvalidArgument`.
+    pub async fn start(&mut self, client: &AscendClient) -> Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Err(AscendError::InvalidArgument("Journal was already started".to_string()));
+        };
+
+        let mut updates = client.subscribe_state().await?;
+        let mut commands = client.speaker_connection().connection().subscribe_commands();
+
+        self.task_handle = Some(tokio::spawn(async move {
+            let mut updates_open = true;
+            let mut commands_open = true;
+            while updates_open || commands_open {
+                let event = tokio::select! {
+                    update = updates.recv(), if updates_open => match update {
+                        Ok(update) => JournalEvent::from_update(update),
+                        Err(AscendError::ConnectionClosed) => {
+                            updates_open = false;
+                            None
+                        }
+                        Err(e) => {
+                            tracing::warn!("Journal missed state updates: {}", e);
+                            None
+                        }
+                    },
+                    command = commands.recv(), if commands_open => match command {
+                        Ok(request) => Some(JournalEvent::from_command(request)),
+                        Err(broadcast::error::RecvError::Closed) => {
+                            commands_open = false;
+                            None
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!("Journal missed {} commands", n);
+                            None
+                        }
+                    },
+                };
+                if let Some(event) = event {
+                    write_entry(&mut writer, event).await;
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stop journaling
+    ///
+    /// Entries already written stay in place; the writer is dropped.
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Write one entry as a line; failures are logged rather than ending the journal
+async fn write_entry(writer: &mut (dyn AsyncWrite + Unpin + Send), event: JournalEvent) {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let entry = JournalEntry { timestamp_ms, event };
+
+    let mut line = match serde_json::to_vec(&entry) {
+        Ok(line) => line,
+        Err(e) => {
+            tracing::warn!("Failed to serialize journal entry: {}", e);
+            return;
+        }
+    };
+    line.push(b'\n');
+
+    let result = match writer.write_all(&line).await {
+        Ok(()) => writer.flush().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to write journal entry: {}", e);
+    }
+}
@@ -5,9 +5,11 @@
 //!
 //! - Discovery via Ascend Cloud API
 //! - Room control via local WebSocket connection
+//! - Rooms of several speakers managed as one collection
 //! - Volume and mute control (global and per-position)
 //! - Voicing profile selection and tone adjustment
 //! - Preset management
+//! - Scenes applying settings across several rooms at once
 //! - Channel mapping configuration
 //! - Real-time state update subscriptions
 //!
@@ -28,10 +30,10 @@
 //!     // Get discovered rooms
 //!     let rooms = discovery.rooms();
 //!     if let Some(discovered_room) = rooms.first() {
-//!         println!("Found room: {}", discovered_room.name);
+//!         println!("Found room: {}", discovered_room.name());
 //!
-//!         // Connect to the discovered room
-//!         let client = discovered_room.connect().await?;
+//!         // Connect a client to the discovered room's speaker
+//!         let client = discovered_room.to_discovered().connect().await?;
 //!         let room_objs = client.rooms().await?;
 //!
 //!         if let Some(room) = room_objs.first() {
@@ -78,28 +80,83 @@
 //! - **Discovery**: Cloud-based room discovery via `wss://api.ascend.audio/`
 //! - **Client**: Connection management and room access
 //! - **Room**: High-level control API for speaker systems
-//! - **Connection**: Low-level WebSocket protocol handling
-//! - **Protocol**: JSON message structures
+//! - **Device**: Control API for individual speakers within a room
+//! - **Connection**: Low-level protocol handling: request/response correlation and notifications
+//! - **Transport**: Message channel a connection runs over (WebSocket by default)
+//! - **Protocol**: JSON message structures and typed endpoint definitions
 //! - **Types**: Domain types and data structures
+//!
+//! # Optional Features
+//!
+//! - `scheduler`: Cron-like scheduling of room actions with pluggable persistence
+//! - `recorder`: Recording of protocol sessions to JSONL files for bug reports
+//! - `journal`: JSON lines log of room state changes and issued commands, for auditing
+//! - `rpc`: JSON-RPC 2.0 server exposing rooms over TCP or stdio
+//! - `cli`: The `ascendctl` command-line tool
+//! - `config`: Static speaker definitions loaded from TOML files
+//! - `ffi`: C ABI with a generated header, for embedding in C and C++ control software
+//! - `webhook`: HTTP notifications of volume, standby, protection, and connection events
+//! - `homeassistant`: Home Assistant MQTT discovery payloads for rooms
+//! - `otel`: Export of request, room setter, and discovery spans over OpenTelemetry OTLP
 
+mod batch;
 mod client;
+#[cfg(feature = "config")]
+pub mod config;
 mod connection;
+mod device;
 mod discovery;
 mod error;
-mod protocol;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod group;
+#[cfg(feature = "homeassistant")]
+pub mod homeassistant;
+#[cfg(feature = "journal")]
+pub mod journal;
+mod manager;
+pub mod protocol;
+#[cfg(feature = "recorder")]
+pub mod recorder;
 mod room;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+mod scenes;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
 mod speaker_connection;
 mod subscription;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+mod transport;
 mod types;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 // Public exports
+pub use batch::RoomBatch;
 pub use client::AscendClient;
+pub use connection::{ConnectOptions, ConnectionStatus, LagPolicy, Priority, ReconnectPolicy};
+pub use device::DeviceHandle;
 pub use discovery::Discovery;
-pub use error::{AscendError, Result};
-pub use room::{Room, RoomState};
-pub use subscription::{StateReceiver, StateUpdate};
+pub use error::{AscendError, RequestContext, Result};
+pub use group::{MemberGain, RoomGroup};
+pub use manager::RoomManager;
+pub use protocol::{Method, Request, Response, TargetType};
+pub use room::{ParseMode, Room, RoomCapabilities, RoomField, RoomState, RoomStateDelta, SlewLimit, SlewMode};
+pub use scenes::{RoomScene, Scene, SceneRunner};
+pub use speaker_connection::SpeakerConnection;
+pub use subscription::{
+    ChangeSource, CoalescingReceiver, RoomReceiver,
+    StandbyCause, StandbyChange, StandbyReceiver, StateReceiver, StateUpdate,
+};
+pub use transport::{Transport, WebSocketTransport};
 pub use types::{
-    ChannelGains, ChannelMapping, Device, DeviceId, DiscoveredRoom, GainData, GainLimits,
-    GainValue, MuteData, MuteState, PositionId, Preset, RoomId, ToneSettings,
-    VoicingProfile,
+    Capabilities, Channel, ChannelGains, ChannelMapping, Device, DeviceId, DiscoveredRoom, Entity,
+    GainData, GainDb, GainLimits,
+    MuteData, MuteState, ParamEq, PositionId, Preset, ProtectionEvent,
+    ProtectionKind, RoomId, RoomMember, SpeakerAddress, SystemInfo, TargetsResponse,
+    ToneAdjustment, ToneSettings, VoicingProfile,
 };
+#[allow(deprecated)]
+pub use types::GainValue;
@@ -0,0 +1,292 @@
+use crate::client::AscendClient;
+use crate::discovery::Discovery;
+use crate::error::{AscendError, Result};
+use crate::room::Room;
+use crate::speaker_connection::SpeakerConnection;
+use crate::subscription::{StateReceiver, StateUpdate};
+use crate::types::{RoomId, SpeakerAddress};
+use futures_util::future::join_all;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Number of merged state updates buffered per subscriber before it lags
+const UPDATE_CAPACITY: usize = 100;
+
+/// A speaker the manager holds a client for
+struct ManagedSpeaker {
+    client: Arc<AscendClient>,
+    /// Task forwarding the speaker's updates to the merged stream
+    forward_task: tokio::task::JoinHandle<()>,
+}
+
+/// Rooms of several speakers, addressable as one collection
+///
+/// Holds a client per speaker (added directly, from `Discovery`, or from a
+/// configuration file) and merges their rooms. Each room is served by the
+/// first speaker that reported it, and commands sent through a `Room` go to
+/// that speaker's connection. `subscribe_state` merges the updates of every
+/// speaker into one stream, leaving out room updates from speakers that don't
+/// serve the room; updates that don't concern a room arrive from each speaker.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::{Discovery, RoomManager};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut discovery = Discovery::new();
+///     discovery.start().await?;
+///     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+///
+///     let manager = RoomManager::new();
+///     manager.add_discovered(&discovery).await;
+///     discovery.stop().await;
+///
+///     let mut updates = manager.subscribe_state();
+///     manager.room_by_name("Kitchen").await?.set_gain(-30.0).await?;
+///     while let Ok(update) = updates.recv().await {
+///         println!("{:?}", update);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct RoomManager {
+    /// Speakers keyed by address (`host:port`)
+    speakers: Mutex<BTreeMap<String, ManagedSpeaker>>,
+    /// Speaker serving each room, by address
+    owners: Arc<Mutex<HashMap<RoomId, String>>>,
+    update_tx: broadcast::Sender<StateUpdate>,
+}
+
+impl Default for RoomManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoomManager {
+    /// Create a manager without speakers
+    pub fn new() -> Self {
+        let (update_tx, _) = broadcast::channel(UPDATE_CAPACITY);
+        Self {
+            speakers: Mutex::new(BTreeMap::new()),
+            owners: Arc::new(Mutex::new(HashMap::new())),
+            update_tx,
+        }
+    }
+
+    /// Connect to a speaker and add it
+    ///
+    /// Does nothing if a speaker at the same address was already added.
+    pub async fn add_speaker(&self, host: impl Into<String>, port: u16) -> Result<()> {
+        let host = host.into();
+        let address = SpeakerAddress { host: host.clone(), port };
+        if self.speakers.lock().unwrap().contains_key(&address.to_string()) {
+            return Ok(());
+        }
+        self.add_client(AscendClient::connect(host, port).await?).await
+    }
+
+    /// Add a speaker over an existing connection, e.g. one opened by `Discovery`
+    ///
+    /// Does nothing if a speaker at the same address was already added.
+    pub async fn add_connection(&self, speaker: Arc<SpeakerConnection>) -> Result<()> {
+        self.add_client(AscendClient::from_connection(speaker).await).await
+    }
+
+    /// Add a connected client's speaker
+    ///
+    /// Does nothing if a speaker at the same address was already added.
+    pub async fn add_client(&self, client: AscendClient) -> Result<()> {
+        let address = client.speaker_address().to_string();
+        if self.speakers.lock().unwrap().contains_key(&address) {
+            return Ok(());
+        }
+
+        let mut updates = client.subscribe_state().await?;
+        let owners = self.owners.clone();
+        let update_tx = self.update_tx.clone();
+        let key = address.clone();
+        let forward_task = tokio::spawn(async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) => {
+                        let owned = update.room_id().is_none_or(|room_id| {
+                            *owners.lock().unwrap().entry(room_id).or_insert_with(|| key.clone()) == key
+                        });
+                        if owned {
+                            let _ = update_tx.send(update);
+                        }
+                    }
+                    Err(AscendError::ConnectionClosed) => break,
+                    Err(e) => tracing::debug!("Room manager missed updates from {}: {}", key, e),
+                }
+            }
+        });
+
+        let managed = ManagedSpeaker {
+            client: Arc::new(client),
+            forward_task,
+        };
+        if let Some(replaced) = self.speakers.lock().unwrap().insert(address, managed) {
+            // Lost a race with a concurrent add of the same speaker
+            replaced.forward_task.abort();
+        }
+        Ok(())
+    }
+
+    /// Add every speaker `Discovery` has connected to so far
+    ///
+    /// Reuses the discovery's connections. Speakers that were already added
+    /// are skipped.
+    pub async fn add_discovered(&self, discovery: &Discovery) {
+        for speaker in discovery.speakers() {
+            if let Err(e) = self.add_connection(speaker).await {
+                tracing::warn!("Failed to add discovered speaker: {}", e);
+            }
+        }
+    }
+
+    /// Connect to every speaker of a configuration file, with its options
+    ///
+    /// Requires the `config` feature. Returns the result of each speaker by
+    /// name, so one unreachable speaker doesn't prevent the others from being
+    /// added.
+    #[cfg(feature = "config")]
+    pub async fn add_configured(&self, config: &crate::config::Config) -> BTreeMap<String, Result<()>> {
+        let mut results = BTreeMap::new();
+        for entry in &config.speakers {
+            let result = match AscendClient::from_config_entry(entry).await {
+                Ok(client) => self.add_client(client).await,
+                Err(e) => Err(e),
+            };
+            results.insert(entry.name.clone(), result);
+        }
+        results
+    }
+
+    /// Remove a speaker, returning whether it was added
+    ///
+    /// Rooms served by the speaker are served by the next speaker that
+    /// reports them.
+    pub fn remove_speaker(&self, address: &SpeakerAddress) -> bool {
+        let address = address.to_string();
+        let Some(removed) = self.speakers.lock().unwrap().remove(&address) else {
+            return false;
+        };
+        removed.forward_task.abort();
+        self.owners.lock().unwrap().retain(|_, owner| *owner != address);
+        true
+    }
+
+    /// Get the addresses of the added speakers
+    pub fn speakers(&self) -> Vec<SpeakerAddress> {
+        self.clients().iter().map(|client| client.speaker_address()).collect()
+    }
+
+    /// Get the client of the speaker serving a room
+    pub fn client_for(&self, room_id: RoomId) -> Option<Arc<AscendClient>> {
+        let owner = self.owners.lock().unwrap().get(&room_id).cloned()?;
+        self.speakers
+            .lock()
+            .unwrap()
+            .get(&owner)
+            .map(|speaker| speaker.client.clone())
+    }
+
+    /// Get the rooms of every speaker keyed by room ID
+    ///
+    /// Speakers whose rooms can't be read are left out, with a warning logged.
+    pub async fn rooms_map(&self) -> BTreeMap<RoomId, Room> {
+        let clients = self.clients();
+        let results = join_all(clients.iter().map(|client| client.rooms_map())).await;
+        let speaker_rooms: Vec<(String, BTreeMap<RoomId, Room>)> = clients
+            .iter()
+            .zip(results)
+            .filter_map(|(client, result)| {
+                let address = client.speaker_address().to_string();
+                match result {
+                    Ok(rooms) => Some((address, rooms)),
+                    Err(e) => {
+                        tracing::warn!("Failed to read rooms from {}: {}", address, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let mut owners = self.owners.lock().unwrap();
+        let mut rooms = BTreeMap::new();
+        for (address, speaker_rooms) in &speaker_rooms {
+            for (room_id, room) in speaker_rooms {
+                if owners.entry(*room_id).or_insert_with(|| address.clone()) == address {
+                    rooms.insert(*room_id, room.clone());
+                }
+            }
+        }
+
+        // Rooms whose speaker didn't answer are served by another speaker that has them
+        for (address, speaker_rooms) in speaker_rooms {
+            for (room_id, room) in speaker_rooms {
+                if let Entry::Vacant(entry) = rooms.entry(room_id) {
+                    owners.insert(room_id, address.clone());
+                    entry.insert(room);
+                }
+            }
+        }
+        rooms
+    }
+
+    /// Get the rooms of every speaker
+    pub async fn rooms(&self) -> Vec<Room> {
+        self.rooms_map().await.into_values().collect()
+    }
+
+    /// Get a room by its ID
+    pub async fn room_by_id(&self, room_id: RoomId) -> Result<Room> {
+        self.rooms_map()
+            .await
+            .remove(&room_id)
+            .ok_or_else(|| AscendError::RoomNotFound(room_id.to_string()))
+    }
+
+    /// Get a room by its name
+    ///
+    /// Fails with `AscendError::AmbiguousRoom` if rooms on several speakers have the name.
+    pub async fn room_by_name(&self, name: &str) -> Result<Room> {
+        let mut matches: Vec<Room> = self.rooms().await.into_iter().filter(|room| room.name() == name).collect();
+        match matches.len() {
+            0 => Err(AscendError::RoomNotFound(name.to_string())),
+            1 => Ok(matches.remove(0)),
+            n => Err(AscendError::AmbiguousRoom(format!("{} rooms named {}; use room_by_id", n, name))),
+        }
+    }
+
+    /// Subscribe to the merged state updates of every speaker
+    ///
+    /// Includes speakers added after subscribing.
+    pub fn subscribe_state(&self) -> StateReceiver {
+        StateReceiver::new(self.update_tx.subscribe())
+    }
+
+    /// Get the clients of the added speakers, in address order
+    fn clients(&self) -> Vec<Arc<AscendClient>> {
+        self.speakers
+            .lock()
+            .unwrap()
+            .values()
+            .map(|speaker| speaker.client.clone())
+            .collect()
+    }
+}
+
+impl Drop for RoomManager {
+    fn drop(&mut self) {
+        for speaker in self.speakers.lock().unwrap().values() {
+            speaker.forward_task.abort();
+        }
+    }
+}
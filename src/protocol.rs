@@ -1,3 +1,5 @@
+use crate::types::{GainDb, MuteState, PositionId, TargetsResponse, ToneSettings};
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
@@ -43,6 +45,9 @@ pub struct ResponseMeta {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "type")]
     pub response_type: Option<String>,
+    /// Sequence number of a notification, on firmware that numbers them
+    #[serde(default, alias = "sequence", skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
 }
 
 /// API error structure
@@ -51,18 +56,216 @@ pub struct ApiError {
     pub detail: String,
 }
 
-/// API endpoint names
+/// An API endpoint with typed request and response payloads
+///
+/// Each implementor is a marker type naming one endpoint/method pair, so a
+/// payload of the wrong shape is a compile error rather than an API failure.
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::protocol::{Gain2, GainUpdate, TypedEndpoint};
+/// use dutchdutch_ascend::{GainDb, Request};
+///
+/// let request = Request::typed::<Gain2>(&GainUpdate { gain: GainDb::new(-20.0) }).unwrap();
+/// assert_eq!(request.meta.endpoint, Gain2::NAME);
+/// ```
+pub trait TypedEndpoint {
+    /// Endpoint name on the wire
+    const NAME: &'static str;
+
+    /// Method the endpoint is invoked with
+    const METHOD: Method;
+
+    /// Request payload (`()` for none)
+    type Request: Serialize;
+
+    /// Response payload (`IgnoredAny` when only the acknowledgement matters)
+    type Response: DeserializeOwned;
+}
+
+/// `{"enable": bool}` payload shared by toggle endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnableUpdate {
+    pub enable: bool,
+}
+
+/// Global gain payload
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GainUpdate {
+    pub gain: GainDb,
+}
+
+/// Mute payload for one position (`"global"` for the whole room)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MuteUpdate {
+    pub mute: MuteState,
+    #[serde(rename = "positionID")]
+    pub position_id: PositionId,
+}
+
+/// Voicing profile selection payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoicingSelect {
+    pub voicing: String,
+}
+
+/// Tone update payload for a voicing profile other than the selected one
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum Endpoint {
-    Discovery,
-    Master,
-    Targets,
-    Gain,
-    Mute,
-    ToneControl,
-    Preset2,
-    ChannelMapping,
+pub struct VoicingToneUpdate {
+    pub voicing: String,
+    #[serde(flatten)]
+    pub tone: ToneSettings,
+}
+
+/// Preset selection payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetSelect {
+    pub id: String,
+}
+
+/// Input selection payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputSelect {
+    pub input: String,
+}
+
+/// XLR mode selection payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XlrSelect {
+    pub xlr: String,
+}
+
+/// `network` read: full network state
+#[derive(Debug, Clone, Copy)]
+pub struct Network;
+
+impl TypedEndpoint for Network {
+    const NAME: &'static str = "network";
+    const METHOD: Method = Method::Read;
+    type Request = ();
+    type Response = Value;
+}
+
+/// `targets` read: controllable rooms and devices
+#[derive(Debug, Clone, Copy)]
+pub struct Targets;
+
+impl TypedEndpoint for Targets {
+    const NAME: &'static str = "targets";
+    const METHOD: Method = Method::Read;
+    type Request = ();
+    type Response = TargetsResponse;
+}
+
+/// `gain2` update: global room gain
+#[derive(Debug, Clone, Copy)]
+pub struct Gain2;
+
+impl TypedEndpoint for Gain2 {
+    const NAME: &'static str = "gain2";
+    const METHOD: Method = Method::Update;
+    type Request = GainUpdate;
+    type Response = IgnoredAny;
+}
+
+/// `mute` update: mute state per position
+#[derive(Debug, Clone, Copy)]
+pub struct Mute;
+
+impl TypedEndpoint for Mute {
+    const NAME: &'static str = "mute";
+    const METHOD: Method = Method::Update;
+    type Request = Vec<MuteUpdate>;
+    type Response = IgnoredAny;
+}
+
+/// `tone-control` update: sub, mid, and treble adjustments
+#[derive(Debug, Clone, Copy)]
+pub struct ToneControl;
+
+impl TypedEndpoint for ToneControl {
+    const NAME: &'static str = "tone-control";
+    const METHOD: Method = Method::Update;
+    type Request = ToneSettings;
+    type Response = IgnoredAny;
+}
+
+/// `tone-control` update: sub, mid, and treble adjustments of a given voicing profile
+#[derive(Debug, Clone, Copy)]
+pub struct VoicingTone;
+
+impl TypedEndpoint for VoicingTone {
+    const NAME: &'static str = "tone-control";
+    const METHOD: Method = Method::Update;
+    type Request = VoicingToneUpdate;
+    type Response = IgnoredAny;
+}
+
+/// `tone-control` select: voicing profile
+#[derive(Debug, Clone, Copy)]
+pub struct Voicing;
+
+impl TypedEndpoint for Voicing {
+    const NAME: &'static str = "tone-control";
+    const METHOD: Method = Method::Select;
+    type Request = VoicingSelect;
+    type Response = IgnoredAny;
+}
+
+/// `preset2` select: apply a preset
+#[derive(Debug, Clone, Copy)]
+pub struct Preset2;
+
+impl TypedEndpoint for Preset2 {
+    const NAME: &'static str = "preset2";
+    const METHOD: Method = Method::Select;
+    type Request = PresetSelect;
+    type Response = IgnoredAny;
+}
+
+/// `selectedInput` update: input source
+#[derive(Debug, Clone, Copy)]
+pub struct SelectedInput;
+
+impl TypedEndpoint for SelectedInput {
+    const NAME: &'static str = "selectedInput";
+    const METHOD: Method = Method::Update;
+    type Request = InputSelect;
+    type Response = IgnoredAny;
+}
+
+/// `selectedXLR` update: XLR input mode
+#[derive(Debug, Clone, Copy)]
+pub struct SelectedXlr;
+
+impl TypedEndpoint for SelectedXlr {
+    const NAME: &'static str = "selectedXLR";
+    const METHOD: Method = Method::Update;
+    type Request = XlrSelect;
+    type Response = IgnoredAny;
+}
+
+/// `sleep` update: standby
+#[derive(Debug, Clone, Copy)]
+pub struct Sleep;
+
+impl TypedEndpoint for Sleep {
+    const NAME: &'static str = "sleep";
+    const METHOD: Method = Method::Update;
+    type Request = EnableUpdate;
+    type Response = IgnoredAny;
+}
+
+/// `linear-phase` update: linear phase filter
+#[derive(Debug, Clone, Copy)]
+pub struct LinearPhase;
+
+impl TypedEndpoint for LinearPhase {
+    const NAME: &'static str = "linear-phase";
+    const METHOD: Method = Method::Update;
+    type Request = EnableUpdate;
+    type Response = IgnoredAny;
 }
 
 /// API methods
@@ -87,6 +290,30 @@ pub enum TargetType {
     Device,
 }
 
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Update => "update",
+            Self::Subscribe => "subscribe",
+            Self::Create => "create",
+            Self::Delete => "delete",
+            Self::Select => "select",
+            Self::Notify => "notify",
+        })
+    }
+}
+
+impl std::fmt::Display for TargetType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Room => "room",
+            Self::Device => "device",
+        })
+    }
+}
+
 impl Request {
     /// Create a new request with the given endpoint and method
     pub fn new(endpoint: impl Into<String>, method: Method) -> Self {
@@ -102,6 +329,17 @@ impl Request {
         }
     }
 
+    /// Create a request for a typed endpoint
+    ///
+    /// A payload that serializes to `null` (such as `()`) is omitted.
+    pub fn typed<E: TypedEndpoint>(payload: &E::Request) -> serde_json::Result<Self> {
+        let request = Self::new(E::NAME, E::METHOD);
+        Ok(match serde_json::to_value(payload)? {
+            Value::Null => request,
+            data => request.with_data(data),
+        })
+    }
+
     /// Set the target type and ID
     pub fn with_target(mut self, target_type: TargetType, target: impl Into<String>) -> Self {
         self.meta.target_type = Some(target_type);
@@ -115,6 +353,28 @@ impl Request {
         self
     }
 
+    /// Replace the generated request ID
+    ///
+    /// Lets callers correlate their own logs with the speaker's: the ID is sent
+    /// as is and reported by errors about the request (see
+    /// `AscendError::request_id`). It must not be shared with another request
+    /// in flight on the same connection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::{Method, Request};
+    /// use uuid::Uuid;
+    ///
+    /// let id = Uuid::new_v4();
+    /// let request = Request::new("gain2", Method::Read).with_id(id);
+    /// assert_eq!(request.id(), id);
+    /// ```
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.meta.id = id;
+        self
+    }
+
     /// Get the request ID
     pub fn id(&self) -> Uuid {
         self.meta.id
@@ -0,0 +1,221 @@
+//! Protocol session recording (requires the `recorder` feature)
+//!
+//! A recording is a JSONL file with one [`RecordedMessage`] per line, covering
+//! every request sent and every response and notification received. Recordings
+//! can be replayed with `AscendClient::replay` to reproduce a session without
+//! hardware.
+
+use crate::error::{AscendError, Result};
+use crate::protocol::{Method, Request};
+use crate::transport::Transport;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Direction of a recorded message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Sent to the speaker (requests)
+    Sent,
+    /// Received from the speaker (responses and notifications)
+    Received,
+}
+
+/// A single line of a session recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedMessage {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+
+    pub direction: Direction,
+
+    /// The message as sent on the wire (a string if it was not valid JSON)
+    pub message: serde_json::Value,
+}
+
+/// Appends messages to a JSONL session file
+pub(crate) struct SessionRecorder {
+    writer: BufWriter<File>,
+}
+
+impl SessionRecorder {
+    /// Open a recording file, appending if it exists
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Record a raw message; failures are logged rather than interrupting the session
+    pub(crate) fn record(&mut self, direction: Direction, text: &str) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let message = serde_json::from_str(text)
+            .unwrap_or_else(|_| serde_json::Value::String(text.to_string()));
+
+        let entry = RecordedMessage {
+            timestamp_ms,
+            direction,
+            message,
+        };
+
+        let result = serde_json::to_writer(&mut self.writer, &entry)
+            .map_err(std::io::Error::from)
+            .and_then(|()| self.writer.write_all(b"\n"))
+            .and_then(|()| self.writer.flush());
+        if let Err(e) = result {
+            tracing::warn!("Failed to record message: {}", e);
+        }
+    }
+}
+
+/// Key matching a live request to recorded responses
+type RequestKey = (String, String, Option<String>);
+
+fn request_key(request: &Request) -> RequestKey {
+    (
+        request.meta.endpoint.clone(),
+        serde_json::to_string(&request.meta.method).unwrap_or_default(),
+        request.meta.target.clone(),
+    )
+}
+
+/// A recorded session prepared for replay
+pub(crate) struct ReplaySession {
+    /// Recorded responses per request, in recorded order
+    responses: HashMap<RequestKey, VecDeque<serde_json::Value>>,
+    /// Received notifications and messages that answered no recorded request
+    notifications: VecDeque<serde_json::Value>,
+    /// Replies waiting to be received by the connection
+    replies: VecDeque<String>,
+}
+
+impl ReplaySession {
+    /// Load a recording and pair each recorded request with its response
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+
+        let mut sent = HashMap::new();
+        let mut received = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: RecordedMessage = serde_json::from_str(&line).map_err(|e| {
+                AscendError::InvalidArgument(format!("Invalid recording line {}: {}", index + 1, e))
+            })?;
+
+            match entry.direction {
+                Direction::Sent => {
+                    if let Ok(request) = serde_json::from_value::<Request>(entry.message) {
+                        sent.insert(request.id(), request_key(&request));
+                    }
+                }
+                Direction::Received => received.push(entry.message),
+            }
+        }
+
+        let mut responses: HashMap<RequestKey, VecDeque<serde_json::Value>> = HashMap::new();
+        let mut notifications = VecDeque::new();
+        for message in received {
+            // Notifications carry the ID of the subscription they belong to,
+            // so they would otherwise be taken for responses to the subscribe
+            let notification = message.pointer("/meta/method").and_then(|method| method.as_str()) == Some("notify");
+            let key = message
+                .pointer("/meta/id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| uuid::Uuid::parse_str(id).ok())
+                .and_then(|id| sent.get(&id));
+            match key {
+                Some(key) if !notification => responses.entry(key.clone()).or_default().push_back(message),
+                _ => notifications.push_back(message),
+            }
+        }
+
+        Ok(Self {
+            responses,
+            notifications,
+            replies: VecDeque::new(),
+        })
+    }
+
+    /// Take the next recorded response for a request, rewritten to its ID
+    ///
+    /// Produces an API error response if nothing was recorded for the request.
+    fn response_for(&mut self, request: &Request) -> String {
+        let id = request.id().to_string();
+        let response = self
+            .responses
+            .get_mut(&request_key(request))
+            .and_then(|queue| queue.pop_front());
+
+        let response = match response {
+            Some(mut response) => {
+                if let Some(meta) = response.get_mut("meta").and_then(|m| m.as_object_mut()) {
+                    meta.insert("id".to_string(), json!(id));
+                }
+                response
+            }
+            None => json!({
+                "meta": { "id": id, "endpoint": request.meta.endpoint, "method": request.meta.method },
+                "errors": [{ "detail": "No recorded response for this request" }],
+            }),
+        };
+        response.to_string()
+    }
+
+    /// Take all recorded notifications not yet delivered
+    fn take_notifications(&mut self) -> Vec<String> {
+        self.notifications.drain(..).map(|n| n.to_string()).collect()
+    }
+}
+
+impl Transport for ReplaySession {
+    async fn send(&mut self, text: String) -> Result<()> {
+        let request: Request = match serde_json::from_str(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Replay received an unparseable request: {}", e);
+                return Ok(());
+            }
+        };
+
+        if request.meta.method == Method::Subscribe {
+            // Acknowledge as recorded, or with an empty response if the
+            // acknowledgment wasn't recorded
+            let acknowledgment = if self.responses.get(&request_key(&request)).is_some_and(|queue| !queue.is_empty()) {
+                self.response_for(&request)
+            } else {
+                json!({
+                    "meta": { "id": request.id(), "endpoint": request.meta.endpoint, "method": request.meta.method },
+                })
+                .to_string()
+            };
+            self.replies.push_back(acknowledgment);
+            let notifications = self.take_notifications();
+            self.replies.extend(notifications);
+        } else {
+            let response = self.response_for(&request);
+            self.replies.push_back(response);
+        }
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Option<Result<String>> {
+        match self.replies.pop_front() {
+            Some(reply) => Some(Ok(reply)),
+            // Nothing more until the next request
+            None => std::future::pending().await,
+        }
+    }
+}
@@ -1,22 +1,91 @@
+use crate::batch::{BatchOp, RoomBatch};
 use crate::error::{AscendError, Result};
-use crate::protocol::{Method, Request, TargetType};
+use crate::protocol::{
+    EnableUpdate, Gain2, GainUpdate,
+    InputSelect, LinearPhase, Method, Mute, MuteUpdate, Network, Preset2,
+    PresetSelect, Request, SelectedInput, SelectedXlr, Sleep, TargetType,
+    ToneControl, TypedEndpoint, Voicing, VoicingSelect, VoicingTone,
+    VoicingToneUpdate, XlrSelect,
+};
 use crate::speaker_connection::SpeakerConnection;
-use crate::types::{ChannelMapping, DeviceId, GainData, GainValue, MuteData, MuteState, Preset, RoomId, ToneSettings, VoicingProfile};
-use serde_json::json;
-use std::collections::BTreeMap;
+use crate::subscription::{RoomReceiver, StandbyReceiver};
+use crate::types::{ChannelMapping, Device, DeviceId, DiscoveredRoom, GainData, GainDb, MuteData, MuteState, ParamEq, Preset, ProductModel, RoomId, RoomMember, SpeakerAddress, ToneAdjustment, ToneSettings, VoicingProfile};
+use crate::subscription::StateUpdate;
+use arc_swap::ArcSwap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::future::Future;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{broadcast, watch};
+
+/// How long confirmed setters wait for the state change to be reported
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of changes `Room::undo` can revert
+const UNDO_DEPTH: usize = 16;
+
+/// Number of changes kept by `Room::history`
+const HISTORY_DEPTH: usize = 100;
+
+/// How far back a sent gain counts as the starting point for the next rise
+const SLEW_WINDOW: Duration = Duration::from_secs(1);
+
+/// Time between steps when a rise is smoothed, and the rise allowed from the
+/// current gain is this interval's worth
+const SLEW_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A value replaced by a state-changing request, restored by `Room::undo`
+#[derive(Debug, Clone)]
+enum UndoEntry {
+    Gain(GainDb),
+    Mute(MuteState),
+    Input(String),
+    Voicing(String),
+}
 
 /// Interface for controlling a room
 ///
 /// A `Room` provides high-level methods for controlling speaker systems,
 /// including volume, mute, voicing profiles, presets, and channel mapping.
+///
+/// Setters optimistically update the local state once the speaker acknowledges
+/// the request, so getters reflect the new value immediately. The next state
+/// notification from the speaker replaces the local state with the reported one.
+///
+/// Concurrent setters on the same room are not ordered unless the connection was
+/// opened with `ConnectOptions::serialize_room_commands`.
 #[derive(Clone)]
 pub struct Room {
     speaker: Arc<SpeakerConnection>,
-    state: Arc<Mutex<RoomState>>,
+    /// Latest state, readable without locking
+    state: Arc<ArcSwap<RoomState>>,
+    /// Latest-value channel mirroring `state` for watchers; also serializes updates
+    watch_tx: Arc<watch::Sender<RoomState>>,
+    /// Values replaced by recent changes, most recent last
+    undo_stack: Arc<Mutex<VecDeque<UndoEntry>>>,
+    /// Recent state changes with the time they were applied, most recent last
+    history: Arc<Mutex<VecDeque<(SystemTime, RoomStateDelta)>>>,
+    /// Cap on how fast the gain may rise, with the last gain sent
+    slew: Arc<Mutex<SlewGuard>>,
+    /// Highest gain setters may send, enforced by this crate
+    safety_limit: Arc<Mutex<Option<GainDb>>>,
+    /// Held across reading, changing, and sending the tone settings
+    tone_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// Slew limit of a room and the last gain sent while it was set
+#[derive(Debug, Default)]
+struct SlewGuard {
+    limit: Option<SlewLimit>,
+    last_sent: Option<(Instant, GainDb)>,
 }
 
 /// Room state snapshot
+///
+/// Collections and the raw JSON are shared between clones, so cloning a
+/// snapshot is cheap regardless of how many presets or profiles a room has.
 #[derive(Clone)]
 pub struct RoomState {
     // Core identity
@@ -24,7 +93,7 @@ pub struct RoomState {
     pub name: String,
 
     // Members is an object mapping device IDs to position IDs
-    pub members: BTreeMap<DeviceId, String>,
+    pub members: Arc<BTreeMap<DeviceId, String>>,
 
     // Gain data with global value and limits
     pub gain: GainData,
@@ -42,28 +111,28 @@ pub struct RoomState {
     pub selected_xlr: Option<String>,
 
     // Raw input modes from JSON (contains all modes including XLR)
-    pub input_modes_raw: Vec<String>,
+    pub input_modes_raw: Arc<Vec<String>>,
 
     // Available input modes (excluding XLR modes) - computed from input_modes_raw
-    pub input_modes: Vec<String>,
+    pub input_modes: Arc<Vec<String>>,
 
     // Available XLR input modes (aes, analogLowGain, analogHighGain) - computed from input_modes_raw
-    pub xlr_input_modes: Vec<String>,
+    pub xlr_input_modes: Arc<Vec<String>>,
 
     // Selected voicing profile ID
     pub selected_voicing_profile: Option<String>,
 
     // Available voicing profiles (map of ID to profile)
-    pub voicing: BTreeMap<String, VoicingProfile>,
+    pub voicing: Arc<BTreeMap<String, VoicingProfile>>,
 
     // Available presets (map of ID to preset)
-    pub presets: BTreeMap<String, Preset>,
+    pub presets: Arc<BTreeMap<String, Preset>>,
 
     // Last selected preset ID
     pub last_selected_preset: Option<String>,
 
     // Channel mapping configuration
-    pub channel_mapping: Option<ChannelMapping>,
+    pub channel_mapping: Option<Arc<ChannelMapping>>,
 
     // Streaming state
     pub streaming: Option<bool>,
@@ -72,120 +141,543 @@ pub struct RoomState {
     pub linear_phase: bool,
 
     // Raw JSON copy
-    pub raw_json: serde_json::Value,
+    pub raw_json: Arc<serde_json::Value>,
+}
+
+/// A field of `RoomState` that can change between snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoomField {
+    Name,
+    Members,
+    Gain,
+    Mute,
+    Sleep,
+    SelectedInput,
+    SelectedXlr,
+    InputModes,
+    SelectedVoicingProfile,
+    Voicing,
+    Presets,
+    LastSelectedPreset,
+    ChannelMapping,
+    Streaming,
+    LinearPhase,
+}
+
+/// The set of fields that changed between two snapshots of a room
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomStateDelta {
+    /// The room the snapshots belong to
+    pub room_id: RoomId,
+
+    /// Fields whose values differ, in declaration order
+    pub changed: Vec<RoomField>,
+}
+
+impl RoomStateDelta {
+    /// Check if no fields changed
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Check if the given field changed
+    pub fn contains(&self, field: RoomField) -> bool {
+        self.changed.contains(&field)
+    }
+}
+
+impl RoomState {
+    /// Parse a room document as found in the `network` state
+    ///
+    /// Unknown fields are ignored and malformed optional fields are left
+    /// unset, as with `ParseMode::Lenient`. Useful for building fixtures from
+    /// captured room documents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::RoomState;
+    /// use serde_json::json;
+    ///
+    /// let state = RoomState::from_json(json!({
+    ///     "id": "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d",
+    ///     "name": "Studio",
+    ///     "gain": { "global": -20.0 },
+    ///     "mute": { "global": false },
+    /// }))
+    /// .unwrap();
+    /// assert_eq!(state.name, "Studio");
+    /// ```
+    pub fn from_json(json: serde_json::Value) -> Result<Self> {
+        parse_room_state_from_json(json, &ParseContext::new(ParseMode::Lenient))
+    }
+
+    /// Get the tone settings of the selected voicing profile
+    ///
+    /// The profile's bass value is the midrange setting. Returns `None` if no
+    /// voicing profile is selected or the selected one isn't listed.
+    pub fn current_tone(&self) -> Option<ToneSettings> {
+        let profile = self.voicing.get(self.selected_voicing_profile.as_ref()?)?;
+        Some(ToneSettings {
+            sub: profile.sub,
+            mid: profile.bass,
+            treble: profile.treble,
+        })
+    }
+
+    /// Store tone settings in the selected voicing profile, as the speaker applies them
+    fn set_current_tone(&mut self, tone: &ToneSettings) {
+        let Some(selected) = self.selected_voicing_profile.clone() else {
+            return;
+        };
+        if let Some(profile) = Arc::make_mut(&mut self.voicing).get_mut(&selected) {
+            profile.sub = tone.sub;
+            profile.bass = tone.mid;
+            profile.treble = tone.treble;
+        }
+    }
+
+    /// Compute which fields changed between two snapshots of the same room
+    ///
+    /// `raw_json` is not compared; only the parsed fields are considered.
+    pub fn diff(old: &RoomState, new: &RoomState) -> RoomStateDelta {
+        let mut changed = Vec::new();
+
+        let mut check = |field: RoomField, differs: bool| {
+            if differs {
+                changed.push(field);
+            }
+        };
+
+        check(RoomField::Name, old.name != new.name);
+        check(RoomField::Members, old.members != new.members);
+        check(RoomField::Gain, old.gain != new.gain);
+        check(RoomField::Mute, old.mute != new.mute);
+        check(RoomField::Sleep, old.sleep != new.sleep);
+        check(RoomField::SelectedInput, old.selected_input != new.selected_input);
+        check(RoomField::SelectedXlr, old.selected_xlr != new.selected_xlr);
+        check(RoomField::InputModes, old.input_modes_raw != new.input_modes_raw);
+        check(
+            RoomField::SelectedVoicingProfile,
+            old.selected_voicing_profile != new.selected_voicing_profile,
+        );
+        check(RoomField::Voicing, old.voicing != new.voicing);
+        check(RoomField::Presets, old.presets != new.presets);
+        check(RoomField::LastSelectedPreset, old.last_selected_preset != new.last_selected_preset);
+        check(RoomField::ChannelMapping, old.channel_mapping != new.channel_mapping);
+        check(RoomField::Streaming, old.streaming != new.streaming);
+        check(RoomField::LinearPhase, old.linear_phase != new.linear_phase);
+
+        RoomStateDelta {
+            room_id: new.id,
+            changed,
+        }
+    }
+}
+
+impl std::fmt::Debug for RoomState {
+    /// Lists voicing profiles and presets by ID and leaves out `raw_json`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoomState")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("members", &self.members)
+            .field("gain", &self.gain)
+            .field("mute", &self.mute)
+            .field("sleep", &self.sleep)
+            .field("selected_input", &self.selected_input)
+            .field("selected_xlr", &self.selected_xlr)
+            .field("input_modes_raw", &self.input_modes_raw)
+            .field("selected_voicing_profile", &self.selected_voicing_profile)
+            .field("voicing", &self.voicing.keys().collect::<Vec<_>>())
+            .field("presets", &self.presets.keys().collect::<Vec<_>>())
+            .field("last_selected_preset", &self.last_selected_preset)
+            .field("channel_mapping", &self.channel_mapping)
+            .field("streaming", &self.streaming)
+            .field("linear_phase", &self.linear_phase)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for Room {
+    /// Formats as e.g. `Living Room (-24.0 dB, XLR, unmuted)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.load();
+        write!(
+            f,
+            "{} ({:.1} dB, {}, {}{})",
+            state.name,
+            state.gain.global,
+            state.selected_input.as_deref().unwrap_or("no input"),
+            if state.mute.global { "muted" } else { "unmuted" },
+            if state.sleep { ", standby" } else { "" },
+        )
+    }
+}
+
+impl std::fmt::Debug for Room {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.load();
+        f.debug_struct("Room")
+            .field("id", &state.id)
+            .field("name", &state.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rooms are equal if they have the same `RoomId`, regardless of their state
+/// or which connection they were obtained from
+impl PartialEq for Room {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Eq for Room {}
+
+impl std::hash::Hash for Room {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
 }
 
 impl Room {
     /// Create a new Room instance from raw JSON
     pub(crate) fn new(speaker: Arc<SpeakerConnection>, json: serde_json::Value) -> Result<Self> {
-        let state = parse_room_state_from_json(json)?;
-        Ok(Self {
+        let state = parse_room_state_from_json(json, speaker.connection().parse_context())?;
+        Ok(Self::with_state(speaker, state))
+    }
+
+    fn with_state(speaker: Arc<SpeakerConnection>, state: RoomState) -> Self {
+        let (watch_tx, _) = watch::channel(state.clone());
+        let safety_limit = speaker.options().max_gain;
+        Self {
             speaker,
-            state: Arc::new(Mutex::new(state)),
-        })
+            state: Arc::new(ArcSwap::from_pointee(state)),
+            watch_tx: Arc::new(watch_tx),
+            undo_stack: Arc::new(Mutex::new(VecDeque::new())),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            slew: Arc::new(Mutex::new(SlewGuard::default())),
+            safety_limit: Arc::new(Mutex::new(safety_limit)),
+            tone_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Create a room that is not connected to a speaker
+    ///
+    /// Getters and `watch` reflect the given state. Setters and other requests
+    /// fail with `AscendError::ConnectionClosed` without changing it. Intended
+    /// for testing UI code against room documents checked into a repository.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::{Room, RoomState};
+    /// use serde_json::json;
+    ///
+    /// let state = RoomState::from_json(json!({
+    ///     "id": "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d",
+    ///     "name": "Studio",
+    ///     "gain": { "global": -20.0 },
+    ///     "mute": { "global": true },
+    /// }))
+    /// .unwrap();
+    /// let room = Room::detached(state);
+    /// assert!(room.mute().global);
+    /// ```
+    pub fn detached(state: RoomState) -> Self {
+        Self::with_state(Arc::new(SpeakerConnection::detached()), state)
     }
 
     /// Get the room ID
     pub fn id(&self) -> uuid::Uuid {
-        self.state.lock().unwrap().id
+        self.state.load().id
     }
 
     /// Get the room name
     pub fn name(&self) -> String {
-        self.state.lock().unwrap().name.clone()
+        self.state.load().name.clone()
+    }
+
+    /// Get the address of the speaker serving this room
+    pub fn speaker_address(&self) -> SpeakerAddress {
+        SpeakerAddress {
+            host: self.speaker.ip().to_string(),
+            port: self.speaker.port(),
+        }
+    }
+
+    /// Describe the room as a `DiscoveredRoom`, e.g. to connect a separate client to it
+    pub fn to_discovered(&self) -> DiscoveredRoom {
+        let state = self.state.load();
+        DiscoveredRoom {
+            id: state.id,
+            name: state.name.clone(),
+            master_ip: self.speaker.ip().to_string(),
+            master_port: self.speaker.port(),
+            members: state.members.keys().cloned().collect(),
+        }
     }
 
     /// Get the raw JSON representation of the room state
     pub fn raw_json(&self) -> serde_json::Value {
-        self.state.lock().unwrap().raw_json.clone()
+        self.state.load().raw_json.as_ref().clone()
     }
 
     /// Get a snapshot of the complete room state for rendering
     /// This ensures consistent values across a single render frame
-    pub fn state_snapshot(&self) -> RoomState {
-        self.state.lock().unwrap().clone()
+    ///
+    /// Taking a snapshot neither locks nor copies the state, so it is cheap
+    /// enough to call on every frame.
+    pub fn state_snapshot(&self) -> Arc<RoomState> {
+        self.state.load_full()
+    }
+
+    /// Watch the room state with latest-value semantics
+    ///
+    /// Unlike the broadcast-based subscriptions, a watch receiver never lags:
+    /// it always yields the most recent state, which suits redraw-on-change GUIs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// let mut state_rx = room.watch();
+    /// while state_rx.changed().await.is_ok() {
+    ///     println!("Volume: {:.1} dB", state_rx.borrow().gain.global);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch(&self) -> watch::Receiver<RoomState> {
+        self.watch_tx.subscribe()
     }
 
     /// Get the gain data including global value, limits, and positional gains
     pub fn gain(&self) -> GainData {
-        self.state.lock().unwrap().gain.clone()
+        self.state.load().gain.clone()
     }
 
     /// Get the mute data including global and per-position mute states
     pub fn mute(&self) -> MuteData {
-        self.state.lock().unwrap().mute.clone()
+        self.state.load().mute.clone()
     }
 
     /// Get the standby/sleep state
     pub fn sleep(&self) -> bool {
-        self.state.lock().unwrap().sleep
+        self.state.load().sleep
     }
 
     /// Get the selected input
     pub fn selected_input(&self) -> Option<String> {
-        self.state.lock().unwrap().selected_input.clone()
+        self.state.load().selected_input.clone()
     }
 
     /// Get the selected XLR mode
     pub fn selected_xlr(&self) -> Option<String> {
-        self.state.lock().unwrap().selected_xlr.clone()
+        self.state.load().selected_xlr.clone()
     }
 
     /// Get the available input modes
     pub fn input_modes(&self) -> Vec<String> {
-        self.state.lock().unwrap().input_modes.clone()
+        self.state.load().input_modes.to_vec()
     }
 
     /// Get the available XLR input modes
     pub fn xlr_input_modes(&self) -> Vec<String> {
-        self.state.lock().unwrap().xlr_input_modes.clone()
+        self.state.load().xlr_input_modes.to_vec()
     }
 
     /// Get the linear phase state
     pub fn linear_phase(&self) -> bool {
-        self.state.lock().unwrap().linear_phase
+        self.state.load().linear_phase
     }
 
     /// Get the number of member devices
     pub fn member_count(&self) -> usize {
-        self.state.lock().unwrap().members.len()
+        self.state.load().members.len()
+    }
+
+    /// Get the member devices with their positions and device details
+    ///
+    /// Device details are looked up from the current network state.
+    pub async fn members(&self) -> Result<Vec<RoomMember>> {
+        let mut devices = self.speaker.request_devices().await?;
+        let members = self.state.load().members.clone();
+
+        Ok(members
+            .iter()
+            .map(|(device_id, position)| RoomMember {
+                device: devices.remove(device_id),
+                device_id: device_id.clone(),
+                position: position.clone(),
+            })
+            .collect())
+    }
+
+    /// Get the hardware features of the room
+    ///
+    /// Looks up the member devices from the current network state; see
+    /// `RoomCapabilities` for how each flag is derived.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// if !room.capabilities().await?.has_sub_output {
+    ///     println!("Hiding the sub level control");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn capabilities(&self) -> Result<RoomCapabilities> {
+        let members = self.members().await?;
+        Ok(RoomCapabilities::derive(&self.state.load(), &members))
     }
 
     /// Get the voicing profiles
     pub fn voicing_profiles(&self) -> BTreeMap<String, VoicingProfile> {
-        self.state.lock().unwrap().voicing.clone()
+        self.state.load().voicing.as_ref().clone()
     }
 
     /// Get the selected voicing profile ID
     pub fn selected_voicing_profile(&self) -> Option<String> {
-        self.state.lock().unwrap().selected_voicing_profile.clone()
+        self.state.load().selected_voicing_profile.clone()
+    }
+
+    /// Get the tone settings of the selected voicing profile
+    ///
+    /// See `RoomState::current_tone`.
+    pub fn current_tone(&self) -> Option<ToneSettings> {
+        self.state.load().current_tone()
     }
 
     /// Get the presets
     pub fn presets(&self) -> BTreeMap<String, Preset> {
-        self.state.lock().unwrap().presets.clone()
+        self.state.load().presets.as_ref().clone()
     }
 
     /// Get the last selected preset ID
     pub fn last_selected_preset(&self) -> Option<String> {
-        self.state.lock().unwrap().last_selected_preset.clone()
+        self.state.load().last_selected_preset.clone()
+    }
+
+    /// Get the last selected preset, if the room lists it
+    pub fn last_selected_preset_info(&self) -> Option<Preset> {
+        let state = self.state.load();
+        state
+            .last_selected_preset
+            .as_ref()
+            .and_then(|id| state.presets.get(id))
+            .cloned()
     }
 
     /// Update the room state from raw JSON (called internally by Discovery when state updates arrive)
+    ///
+    /// Partial documents are merged into the current state; see
+    /// `merge_room_state_from_json`.
     pub(crate) fn update_from_json(&self, json: serde_json::Value) -> Result<()> {
-        let new_state = parse_room_state_from_json(json)?;
-        *self.state.lock().unwrap() = new_state;
-        Ok(())
+        let connection = self.speaker.connection();
+        let mut result = Ok(());
+        self.watch_tx.send_if_modified(|state| match merge_room_state_from_json(state, json, connection.parse_context()) {
+            Ok(merged) => {
+                // The merged document is kept either way, but watchers are
+                // only woken if a field they can see changed
+                let changed = !RoomState::diff(state, &merged).is_empty();
+                self.record_history(state, &merged);
+                *state = merged;
+                self.state.store(Arc::new(state.clone()));
+                changed
+            }
+            Err(e) => {
+                result = Err(e);
+                false
+            }
+        });
+        result
     }
 
-    /// Refresh the room state from the speaker
-    pub async fn refresh(&mut self) -> Result<()> {
-        let request = Request::new("network", Method::Read);
-        let response = self.speaker.connection().send_request(request).await?;
+    /// Apply a change to the local room state and notify watchers
+    ///
+    /// The changed fields are written back into `raw_json`, so the next room
+    /// document is compared against, and a partial one merged onto, the state
+    /// as shown rather than as last reported.
+    fn update_state(&self, f: impl FnOnce(&mut RoomState)) {
+        // The watch channel's lock orders concurrent updates
+        self.watch_tx.send_modify(|state| {
+            let previous = state.clone();
+            f(state);
+            let delta = RoomState::diff(&previous, state);
+            if !delta.is_empty() {
+                let mut raw_json = state.raw_json.as_ref().clone();
+                for field in &delta.changed {
+                    patch_room_json(&mut raw_json, state, *field);
+                }
+                state.raw_json = Arc::new(raw_json);
+            }
+            self.record_history(&previous, state);
+            self.state.store(Arc::new(state.clone()));
+        });
+    }
+
+    /// Record the fields that differ between two states in the history
+    fn record_history(&self, old: &RoomState, new: &RoomState) {
+        let delta = RoomState::diff(old, new);
+        if delta.is_empty() {
+            return;
+        }
+        let mut history = self.history.lock().unwrap();
+        if history.len() == HISTORY_DEPTH {
+            history.pop_front();
+        }
+        history.push_back((SystemTime::now(), delta));
+    }
+
+    /// Get the most recent changes to the room, oldest first
+    ///
+    /// Every change is recorded with the time it was applied, whether it was
+    /// made through this handle or reported by the speaker, up to the last
+    /// 100. Handles to the same room from one client or `Discovery` share the
+    /// history.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, RoomField};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// for (time, delta) in room.history() {
+    ///     if delta.contains(RoomField::Gain) {
+    ///         println!("{:?}: gain changed", time);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn history(&self) -> Vec<(SystemTime, RoomStateDelta)> {
+        self.history.lock().unwrap().iter().cloned().collect()
+    }
 
-        let data = response
-            .data
-            .ok_or_else(|| AscendError::InvalidResponse("No data in network response".to_string()))?;
+    /// Refresh the room state from the speaker
+    ///
+    /// All clones of this `Room` observe the refreshed state.
+    pub async fn refresh(&self) -> Result<()> {
+        let data = self
+            .speaker
+            .connection()
+            .send_typed::<Network>(Request::typed::<Network>(&())?)
+            .await?;
 
         // Parse the state to find our room
         let state = data
@@ -197,7 +689,7 @@ impl Room {
             .ok_or_else(|| AscendError::InvalidResponse("State is not an object".to_string()))?;
 
         // Find our room by ID
-        let current_id = self.state.lock().unwrap().id;
+        let current_id = self.state.load().id;
         for (_state_id, state_entry) in state_obj {
             if let Some(entry_data) = state_entry.get("data") {
                 if entry_data.get("type").and_then(|v| v.as_str()) == Some("room") {
@@ -216,6 +708,241 @@ impl Room {
         Err(AscendError::RoomNotFound(current_id.to_string()))
     }
 
+    /// Start a batch of changes sent back-to-back (see `RoomBatch`)
+    pub fn batch(&self) -> RoomBatch {
+        RoomBatch::new(self.clone())
+    }
+
+    /// Send a typed request and apply the change to the local state
+    ///
+    /// The local update happens while the room's command queue is still held,
+    /// so serialized commands also update the state in order.
+    async fn apply<E: TypedEndpoint>(&self, payload: &E::Request, f: impl FnOnce(&mut RoomState)) -> Result<()> {
+        let _queued = self.speaker.connection().queue_room_command(self.id()).await;
+        self.send::<E>(payload).await?;
+        self.update_state(f);
+        Ok(())
+    }
+
+    /// Send a typed request targeted at this room without queueing
+    async fn send<E: TypedEndpoint>(&self, payload: &E::Request) -> Result<E::Response> {
+        let request = self.request::<E>(payload)?;
+        self.speaker.connection().send_typed::<E>(request).await
+    }
+
+    /// Build a typed request targeted at this room
+    fn request<E: TypedEndpoint>(&self, payload: &E::Request) -> Result<Request> {
+        Ok(Request::typed::<E>(payload)?.with_target(TargetType::Room, self.state.load().id.to_string()))
+    }
+
+    /// Send the changes of a batch in order and wait for every response
+    ///
+    /// The room's command queue is held for the whole batch. Each change is
+    /// checked like its setter first; one that fails the check isn't sent,
+    /// and the others still are. Accepted changes are applied to the local
+    /// state and recorded for undo in order.
+    pub(crate) async fn send_batch(&self, ops: Vec<BatchOp>) -> Vec<Result<()>> {
+        let _queued = self.speaker.connection().queue_room_command(self.id()).await;
+        let requests = ops.iter().map(|op| self.batch_request(op)).collect();
+        let responses = self.speaker.connection().send_requests(requests).await;
+
+        ops.into_iter()
+            .zip(responses)
+            .map(|(op, response)| {
+                response?;
+                self.apply_batch_op(op);
+                Ok(())
+            })
+            .collect()
+    }
+
+    /// Check a batched change and build its request
+    ///
+    /// A gain rise beyond the slew limit is rejected even when smoothing, as
+    /// a batch can't ramp.
+    fn batch_request(&self, op: &BatchOp) -> Result<Request> {
+        match op {
+            BatchOp::Gain(gain) => {
+                let gain = *gain;
+                if let Some(limit) = self.safety_limit() {
+                    if gain > limit {
+                        return Err(AscendError::SafetyLimit { requested: gain, limit });
+                    }
+                }
+                if let Err((_, allowed)) = self.slew_step(gain) {
+                    return Err(AscendError::SlewRateExceeded { requested: gain, allowed });
+                }
+                self.request::<Gain2>(&GainUpdate { gain })
+            }
+            BatchOp::Mute(mute) => self.request::<Mute>(&vec![MuteUpdate {
+                mute: *mute,
+                position_id: "global".to_string(),
+            }]),
+            BatchOp::Input(input) => self.request::<SelectedInput>(&InputSelect { input: input.clone() }),
+            BatchOp::Voicing(profile) => self.request::<Voicing>(&VoicingSelect {
+                voicing: profile.clone(),
+            }),
+            BatchOp::Preset(preset_id) => self.request::<Preset2>(&PresetSelect { id: preset_id.clone() }),
+            BatchOp::Standby(standby) => self.request::<Sleep>(&EnableUpdate { enable: *standby }),
+        }
+    }
+
+    /// Apply an accepted batched change to the local state, recording it for undo
+    fn apply_batch_op(&self, op: BatchOp) {
+        let state = self.state.load();
+        match op {
+            BatchOp::Gain(gain) => {
+                self.record_undo(UndoEntry::Gain(state.gain.global));
+                self.update_state(|state| state.gain.global = gain);
+                self.note_gain_sent(gain);
+            }
+            BatchOp::Mute(mute) => {
+                self.record_undo(UndoEntry::Mute(state.mute.global));
+                self.update_state(|state| state.mute.global = mute);
+            }
+            BatchOp::Input(input) => {
+                if let Some(previous) = state.selected_input.clone() {
+                    self.record_undo(UndoEntry::Input(previous));
+                }
+                self.update_state(|state| state.selected_input = Some(input));
+            }
+            BatchOp::Voicing(profile) => {
+                if let Some(previous) = state.selected_voicing_profile.clone() {
+                    self.record_undo(UndoEntry::Voicing(previous));
+                }
+                self.update_state(|state| state.selected_voicing_profile = Some(profile));
+            }
+            BatchOp::Preset(preset_id) => self.update_state(|state| state.last_selected_preset = Some(preset_id)),
+            BatchOp::Standby(standby) => self.update_state(|state| state.sleep = standby),
+        }
+    }
+
+    /// Send a raw request targeted at this room and return the response data
+    ///
+    /// This is an escape hatch for endpoints the crate does not model yet. The
+    /// room target is applied automatically. Returns `Value::Null` if the
+    /// response carries no data.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, Method};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// let data = room.send_raw("gain2", Method::Read, None).await?;
+    /// println!("{}", data);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_raw(
+        &self,
+        endpoint: impl Into<String>,
+        method: Method,
+        data: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.send_raw_with_id(endpoint, method, data, uuid::Uuid::new_v4()).await
+    }
+
+    /// Send a raw request targeted at this room with a caller-chosen request ID
+    ///
+    /// Like `send_raw`, but the request carries `id`, so it can be matched
+    /// with the caller's logs and the speaker's. Errors about the request
+    /// report the ID through `AscendError::request_id`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, Method};
+    /// # use uuid::Uuid;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// let id = Uuid::new_v4();
+    /// if let Err(e) = room.send_raw_with_id("gain2", Method::Read, None, id).await {
+    ///     eprintln!("request {} failed: {}", id, e);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_raw_with_id(
+        &self,
+        endpoint: impl Into<String>,
+        method: Method,
+        data: Option<serde_json::Value>,
+        id: uuid::Uuid,
+    ) -> Result<serde_json::Value> {
+        let mut request = Request::new(endpoint, method)
+            .with_id(id)
+            .with_target(TargetType::Room, self.state.load().id.to_string());
+        if let Some(data) = data {
+            request = request.with_data(data);
+        }
+
+        let response = self.speaker.connection().send_request(request).await?;
+        Ok(response.data.unwrap_or(serde_json::Value::Null))
+    }
+
+    // ========== Metering ==========
+
+    /// Subscribe to state updates for this room only
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, StateUpdate};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// let mut updates = room.subscribe().await?;
+    /// while let Ok(update) = updates.recv().await {
+    ///     if let StateUpdate::GainChanged { gain, .. } = update {
+    ///         println!("Gain: {} dB", gain.global);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe(&self) -> Result<RoomReceiver> {
+        let room_id = self.state.load().id;
+        self.speaker.subscribe_room(room_id).await
+    }
+
+    /// Subscribe to the room entering and leaving standby
+    ///
+    /// Reports every change with its cause, including the speaker going to
+    /// sleep on its own after an idle timeout (as `StandbyCause::External`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, StandbyCause};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// let mut changes = room.on_standby_change().await?;
+    /// while let Ok(change) = changes.recv().await {
+    ///     if change.sleeping && change.cause == StandbyCause::External {
+    ///         println!("Standby, switching off the amplifier");
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn on_standby_change(&self) -> Result<StandbyReceiver> {
+        let room_id = self.state.load().id;
+        let connection = self.speaker.connection();
+        let rx = connection.subscribe();
+        connection
+            .subscribe_request(Request::new("network", Method::Subscribe))
+            .await?;
+        Ok(StandbyReceiver::new(rx, room_id))
+    }
+
     // ========== Volume Control ==========
 
     /// Set the global room volume in dB
@@ -232,12 +959,12 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn set_gain(&self, gain: GainValue) -> Result<()> {
-        let request = Request::new("gain2", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "gain": gain }));
-
-        self.speaker.connection().send_request(request).await?;
+    #[tracing::instrument(skip(self, gain), fields(room = %self.id()), err(Display, level = "debug"))]
+    pub async fn set_gain(&self, gain: impl Into<GainDb>) -> Result<()> {
+        let gain = gain.into();
+        let previous = self.state.load().gain.global;
+        self.send_gain(gain).await?;
+        self.record_undo(UndoEntry::Gain(previous));
         Ok(())
     }
 
@@ -257,15 +984,11 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn set_mute(&self, mute: MuteState) -> Result<()> {
-        let request = Request::new("mute", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!([{
-                "mute": mute,
-                "positionID": "global"
-            }]));
-
-        self.speaker.connection().send_request(request).await?;
+        let previous = self.state.load().mute.global;
+        self.send_mute(mute).await?;
+        self.record_undo(UndoEntry::Mute(previous));
         Ok(())
     }
 
@@ -285,13 +1008,10 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn set_standby(&self, standby: bool) -> Result<()> {
-        let request = Request::new("sleep", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "enable": standby }));
-
-        self.speaker.connection().send_request(request).await?;
-        Ok(())
+        self.apply::<Sleep>(&EnableUpdate { enable: standby }, |state| state.sleep = standby)
+            .await
     }
 
     // ========== Input Selection ==========
@@ -310,12 +1030,14 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self, input), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn set_input(&self, input: impl Into<String>) -> Result<()> {
-        let request = Request::new("selectedInput", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "input": input.into() }));
-
-        self.speaker.connection().send_request(request).await?;
+        let input = input.into();
+        let previous = self.state.load().selected_input.clone();
+        self.send_input(input).await?;
+        if let Some(previous) = previous {
+            self.record_undo(UndoEntry::Input(previous));
+        }
         Ok(())
     }
 
@@ -329,17 +1051,17 @@ impl Room {
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
     /// let room = client.room().await?;
-    /// room.set_xlr_input("aes").await?;
+    /// room.set_xlr_mode("aes").await?;
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self, mode), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn set_xlr_mode(&self, mode: impl Into<String>) -> Result<()> {
-        let request = Request::new("selectedXLR", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "xlr": mode.into() }));
-
-        self.speaker.connection().send_request(request).await?;
-        Ok(())
+        let mode = mode.into();
+        self.apply::<SelectedXlr>(&XlrSelect { xlr: mode.clone() }, |state| {
+            state.selected_xlr = Some(mode)
+        })
+        .await
     }
 
     /// Set the linear phase filter state
@@ -356,13 +1078,13 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn set_linear_phase(&self, enabled: bool) -> Result<()> {
-        let request = Request::new("linear-phase", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "enable": enabled }));
-
-        self.speaker.connection().send_request(request).await?;
-        Ok(())
+        self.speaker.require(|c| c.supports_linear_phase, "linear phase")?;
+        self.apply::<LinearPhase>(&EnableUpdate { enable: enabled }, |state| {
+            state.linear_phase = enabled
+        })
+        .await
     }
 
     /// Select a voicing profile
@@ -379,12 +1101,13 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self, profile), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn select_voicing(&self, profile: impl Into<String>) -> Result<()> {
-        let request = Request::new("tone-control", Method::Select)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "voicing": profile.into() }));
-
-        self.speaker.connection().send_request(request).await?;
+        let previous = self.state.load().selected_voicing_profile.clone();
+        self.send_voicing(profile.into()).await?;
+        if let Some(previous) = previous {
+            self.record_undo(UndoEntry::Voicing(previous));
+        }
         Ok(())
     }
 
@@ -406,21 +1129,124 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn update_tone(&self, tone: ToneSettings) -> Result<()> {
-        let request = Request::new("tone-control", Method::Update)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(serde_json::to_value(&tone)?);
-
-        self.speaker.connection().send_request(request).await?;
-        Ok(())
+        let _tone = self.tone_lock.lock().await;
+        self.apply::<ToneControl>(&tone, |state| state.set_current_tone(&tone))
+            .await
     }
 
-    /// Select and apply a preset
+    /// Change some tone control settings, keeping the others
+    ///
+    /// Unset fields are taken from the selected voicing profile, whose bass
+    /// value is the current midrange setting. Fails if no voicing profile is
+    /// selected.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use dutchdutch_ascend::AscendClient;
+    /// # use dutchdutch_ascend::{AscendClient, ToneAdjustment, ToneSettings};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.adjust_tone(ToneAdjustment { treble: Some(1.0), ..Default::default() }).await?;
+    /// room.adjust_tone(ToneSettings::builder().with_sub(2.0)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[tracing::instrument(skip(self), fields(room = %self.id()), err(Display, level = "debug"))]
+    pub async fn adjust_tone(&self, adjustment: ToneAdjustment) -> Result<()> {
+        let _tone = self.tone_lock.lock().await;
+        let _queued = self.speaker.connection().queue_room_command(self.id()).await;
+        let current = self
+            .current_tone()
+            .ok_or_else(|| AscendError::InvalidArgument("No voicing profile is selected".to_string()))?;
+        self.send_tone(adjustment.apply_to(&current)).await
+    }
+
+    /// Raise or lower the bass of the selected voicing profile by `delta` dB
+    ///
+    /// The result is clamped to `ToneSettings::MIN`..=`ToneSettings::MAX`, so
+    /// repeated steps, e.g. from a rotary encoder, stop at the end of the range.
+    /// Steps sent in quick succession build on each other without waiting for
+    /// the speaker to report the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.adjust_bass(0.5).await?;
+    /// room.adjust_treble(-0.5).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn adjust_bass(&self, delta: f64) -> Result<()> {
+        self.step_tone(delta, |tone| &mut tone.mid).await
+    }
+
+    /// Raise or lower the treble of the selected voicing profile by `delta` dB
+    ///
+    /// See `adjust_bass`.
+    pub async fn adjust_treble(&self, delta: f64) -> Result<()> {
+        self.step_tone(delta, |tone| &mut tone.treble).await
+    }
+
+    /// Raise or lower the subwoofer level of the selected voicing profile by `delta` dB
+    ///
+    /// See `adjust_bass`.
+    pub async fn adjust_sub(&self, delta: f64) -> Result<()> {
+        self.step_tone(delta, |tone| &mut tone.sub).await
+    }
+
+    /// Update the tone control settings of a voicing profile without selecting it
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, ToneSettings};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.set_tone_for_voicing("night", ToneSettings {
+    ///     sub: -3.0,
+    ///     mid: 0.0,
+    ///     treble: -1.0,
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[tracing::instrument(skip(self, voicing), fields(room = %self.id()), err(Display, level = "debug"))]
+    pub async fn set_tone_for_voicing(&self, voicing: impl Into<String>, tone: ToneSettings) -> Result<()> {
+        let voicing = voicing.into();
+        if !self.state.load().voicing.contains_key(&voicing) {
+            return Err(AscendError::InvalidArgument(format!("Unknown voicing profile: {}", voicing)));
+        }
+        let update = VoicingToneUpdate {
+            voicing: voicing.clone(),
+            tone: tone.clone(),
+        };
+        self.apply::<VoicingTone>(&update, |state| {
+            if let Some(profile) = Arc::make_mut(&mut state.voicing).get_mut(&voicing) {
+                profile.sub = tone.sub;
+                profile.bass = tone.mid;
+                profile.treble = tone.treble;
+            }
+        })
+        .await
+    }
+
+    /// Select and apply a preset
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
@@ -429,18 +1255,692 @@ impl Room {
     /// # Ok(())
     /// # }
     /// ```
+    #[tracing::instrument(skip(self, preset_id), fields(room = %self.id()), err(Display, level = "debug"))]
     pub async fn select_preset(&self, preset_id: impl Into<String>) -> Result<()> {
-        let request = Request::new("preset2", Method::Select)
-            .with_target(TargetType::Room, self.state.lock().unwrap().id.to_string())
-            .with_data(json!({ "id": preset_id.into() }));
+        let preset_id = preset_id.into();
+        self.apply::<Preset2>(&PresetSelect { id: preset_id.clone() }, |state| {
+            state.last_selected_preset = Some(preset_id)
+        })
+        .await?;
+        self.enforce_safety_limit().await
+    }
 
-        self.speaker.connection().send_request(request).await?;
+    /// Lower the gain to the safety limit if a preset left it above
+    ///
+    /// The speaker applies a preset's own gain without a gain request, so the
+    /// room is refreshed to see the gain it ended up at.
+    async fn enforce_safety_limit(&self) -> Result<()> {
+        let Some(limit) = self.safety_limit() else {
+            return Ok(());
+        };
+        self.refresh().await?;
+
+        let gain = self.state.load().gain.global;
+        if gain <= limit {
+            return Ok(());
+        }
+        tracing::warn!("Preset raised room {} to {}, lowering it to the safety limit of {}", self.id(), gain, limit);
+        self.send_gain_now(limit).await
+    }
+
+    // ========== Undo ==========
+
+    /// Revert the most recent gain, mute, input, or voicing change
+    ///
+    /// Changes made through this handle (or any clone of it) are recorded, up
+    /// to the last 16. Returns `false` if there is nothing to undo.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::AscendClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.set_gain(-10.0).await?;
+    /// room.undo().await?; // back to the previous volume
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn undo(&self) -> Result<bool> {
+        let entry = self.undo_stack.lock().unwrap().pop_back();
+        let Some(entry) = entry else {
+            return Ok(false);
+        };
+
+        let result = match entry.clone() {
+            UndoEntry::Gain(gain) => self.send_gain(gain).await,
+            UndoEntry::Mute(mute) => self.send_mute(mute).await,
+            UndoEntry::Input(input) => self.send_input(input).await,
+            UndoEntry::Voicing(profile) => self.send_voicing(profile).await,
+        };
+
+        if result.is_err() {
+            // Keep the entry so the undo can be retried
+            self.undo_stack.lock().unwrap().push_back(entry);
+        }
+        result.map(|()| true)
+    }
+
+    /// Record the value a change replaced, dropping the oldest entry when full
+    fn record_undo(&self, entry: UndoEntry) {
+        let mut undo_stack = self.undo_stack.lock().unwrap();
+        if undo_stack.len() == UNDO_DEPTH {
+            undo_stack.pop_front();
+        }
+        undo_stack.push_back(entry);
+    }
+
+    /// Refuse to raise the gain above `max_db`
+    ///
+    /// Applies to every gain change made through the room, including batches,
+    /// scenes, slew-limited ramps, and `undo`: a change to a gain above the
+    /// limit fails with `AscendError::SafetyLimit` and nothing is sent.
+    /// `RoomGroup` clamps each room's share of a group change to the limit
+    /// instead. Selecting a preset, directly or through a scene, refreshes the
+    /// room afterwards and lowers its gain to the limit if the preset raised it
+    /// above.
+    /// Rooms start out with `ConnectOptions::max_gain` as their limit, which
+    /// this replaces. The limit is independent of the speaker's own gain limits and
+    /// does not stop other controllers, such as the phone app, from going
+    /// higher. Handles to the same room from one client or `Discovery` share
+    /// the limit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, AscendError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.set_safety_limit(-20.0);
+    /// assert!(matches!(room.set_gain(-10.0).await, Err(AscendError::SafetyLimit { .. })));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_safety_limit(&self, max_db: impl Into<GainDb>) {
+        *self.safety_limit.lock().unwrap() = Some(max_db.into());
+    }
+
+    /// Remove the safety limit set with `set_safety_limit`
+    pub fn clear_safety_limit(&self) {
+        *self.safety_limit.lock().unwrap() = None;
+    }
+
+    /// Get the safety limit, if one is set
+    pub fn safety_limit(&self) -> Option<GainDb> {
+        *self.safety_limit.lock().unwrap()
+    }
+
+    /// Cap how fast the gain may rise
+    ///
+    /// Applies to every gain change made through the room, including batches,
+    /// groups, scenes, and `undo`. Lowering the gain is never limited. A rise
+    /// is measured from the last gain sent if that was within the last second,
+    /// allowing the rate times the time since. Otherwise it is measured from
+    /// the current gain, allowing one 100 ms step's worth, so a 6 dB/s limit
+    /// lets an idle room rise by 0.6 dB at once. `None` removes the limit. Handles to the same room
+    /// from one client or `Discovery` share the limit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, SlewLimit};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let room = client.room().await?;
+    /// room.set_slew_limit(Some(SlewLimit::smooth(6.0)));
+    /// room.set_gain(-40.0).await?;
+    /// room.set_gain(-20.0).await?; // ramps up at 6 dB per second
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_slew_limit(&self, limit: Option<SlewLimit>) {
+        let mut slew = self.slew.lock().unwrap();
+        slew.limit = limit;
+        slew.last_sent = None;
+    }
+
+    /// Get the cap on how fast the gain may rise, if one is set
+    pub fn slew_limit(&self) -> Option<SlewLimit> {
+        self.slew.lock().unwrap().limit
+    }
+
+    /// Send a global gain change without recording it for undo
+    ///
+    /// Gains above the safety limit are rejected. Rises faster than the slew
+    /// limit are rejected, or sent in steps when smoothing.
+    async fn send_gain(&self, gain: GainDb) -> Result<()> {
+        if let Some(limit) = self.safety_limit() {
+            if gain > limit {
+                return Err(AscendError::SafetyLimit { requested: gain, limit });
+            }
+        }
+        loop {
+            let step = match self.slew_step(gain) {
+                Ok(step) => step,
+                Err((SlewMode::Reject, allowed)) => {
+                    return Err(AscendError::SlewRateExceeded { requested: gain, allowed });
+                }
+                Err((SlewMode::Smooth, allowed)) => {
+                    self.send_gain_now(allowed).await?;
+                    tokio::time::sleep(SLEW_STEP_INTERVAL).await;
+                    continue;
+                }
+            };
+            return self.send_gain_now(step).await;
+        }
+    }
+
+    /// Check a gain against the slew limit
+    ///
+    /// On failure, returns the limit's mode and the highest gain allowed now.
+    fn slew_step(&self, gain: GainDb) -> std::result::Result<GainDb, (SlewMode, GainDb)> {
+        let slew = self.slew.lock().unwrap();
+        let Some(limit) = slew.limit else {
+            return Ok(gain);
+        };
+
+        let allowed = match slew.last_sent {
+            Some((sent_at, sent)) if sent_at.elapsed() < SLEW_WINDOW => {
+                sent + GainDb::new(limit.max_rise_per_sec * sent_at.elapsed().as_secs_f64())
+            }
+            _ => {
+                let step = limit.max_rise_per_sec * SLEW_STEP_INTERVAL.as_secs_f64();
+                self.state.load().gain.global + GainDb::new(step)
+            }
+        };
+        if gain <= allowed {
+            return Ok(gain);
+        }
+        // Without a rate to ramp at, smoothing would never arrive
+        let mode = if limit.max_rise_per_sec > 0.0 { limit.mode } else { SlewMode::Reject };
+        Err((mode, allowed))
+    }
+
+    /// Note a gain as sent, as the starting point for the next rise
+    fn note_gain_sent(&self, gain: GainDb) {
+        let mut slew = self.slew.lock().unwrap();
+        if slew.limit.is_some() {
+            slew.last_sent = Some((Instant::now(), gain));
+        }
+    }
+
+    /// Send a global gain change as is
+    ///
+    /// The gain is only noted for the slew limit once the speaker accepted it.
+    async fn send_gain_now(&self, gain: GainDb) -> Result<()> {
+        self.apply::<Gain2>(&GainUpdate { gain }, |state| state.gain.global = gain)
+            .await?;
+        self.note_gain_sent(gain);
+        Ok(())
+    }
+
+    /// Send a global mute change without recording it for undo
+    async fn send_mute(&self, mute: MuteState) -> Result<()> {
+        let update = vec![MuteUpdate {
+            mute,
+            position_id: "global".to_string(),
+        }];
+        self.apply::<Mute>(&update, |state| state.mute.global = mute).await
+    }
+
+    /// Send a input selection change without recording it for undo
+    async fn send_input(&self, input: String) -> Result<()> {
+        self.apply::<SelectedInput>(&InputSelect { input: input.clone() }, |state| {
+            state.selected_input = Some(input)
+        })
+        .await
+    }
+
+    /// Change one tone control of the selected voicing profile by `delta`
+    async fn step_tone(&self, delta: f64, control: fn(&mut ToneSettings) -> &mut f64) -> Result<()> {
+        if !delta.is_finite() {
+            return Err(AscendError::InvalidArgument(format!("Invalid tone step: {}", delta)));
+        }
+
+        let _tone = self.tone_lock.lock().await;
+        let _queued = self.speaker.connection().queue_room_command(self.id()).await;
+        let mut tone = self
+            .current_tone()
+            .ok_or_else(|| AscendError::InvalidArgument("No voicing profile is selected".to_string()))?;
+        *control(&mut tone) += delta;
+        self.send_tone(tone.clamped()).await
+    }
+
+    /// Send tone settings and store them in the selected voicing profile, without queueing
+    ///
+    /// Callers hold the room's tone lock across reading the current settings
+    /// and sending, so concurrent changes don't overwrite each other whether
+    /// or not room commands are serialized.
+    async fn send_tone(&self, tone: ToneSettings) -> Result<()> {
+        self.send::<ToneControl>(&tone).await?;
+        self.update_state(|state| state.set_current_tone(&tone));
         Ok(())
     }
+
+    /// Send a voicing selection change without recording it for undo
+    async fn send_voicing(&self, profile: String) -> Result<()> {
+        self.apply::<Voicing>(
+            &VoicingSelect {
+                voicing: profile.clone(),
+            },
+            |state| state.selected_voicing_profile = Some(profile),
+        )
+        .await
+    }
+
+    // ========== Confirmed Setters ==========
+    //
+    // These variants resolve once a subscription update shows the requested
+    // value, rather than when the speaker acknowledges the request. They rely
+    // on an active state subscription (as set up by `Discovery` or
+    // `AscendClient::subscribe_state`) and fail with `AscendError::Timeout`
+    // if the change is not reported in time.
+
+    /// Set the global room volume in dB and wait for the change to be reported
+    pub async fn set_gain_confirmed(&self, gain: impl Into<GainDb>) -> Result<()> {
+        let gain = gain.into();
+        self.confirm(self.set_gain(gain), move |state| {
+            (state.gain.global - gain).abs().db() <= state.gain.step() / 2.0
+        })
+        .await
+    }
+
+    /// Set the global room mute state and wait for the change to be reported
+    pub async fn set_mute_confirmed(&self, mute: MuteState) -> Result<()> {
+        self.confirm(self.set_mute(mute), move |state| state.mute.global == mute)
+            .await
+    }
+
+    /// Set the standby/sleep state and wait for the change to be reported
+    pub async fn set_standby_confirmed(&self, standby: bool) -> Result<()> {
+        self.confirm(self.set_standby(standby), move |state| state.sleep == standby)
+            .await
+    }
+
+    /// Set the selected input source and wait for the change to be reported
+    pub async fn set_input_confirmed(&self, input: impl Into<String>) -> Result<()> {
+        let input = input.into();
+        let expected = input.clone();
+        self.confirm(self.set_input(input), move |state| {
+            state.selected_input.as_deref() == Some(expected.as_str())
+        })
+        .await
+    }
+
+    /// Set the linear phase filter state and wait for the change to be reported
+    pub async fn set_linear_phase_confirmed(&self, enabled: bool) -> Result<()> {
+        self.confirm(self.set_linear_phase(enabled), move |state| state.linear_phase == enabled)
+            .await
+    }
+
+    /// Select a voicing profile and wait for the change to be reported
+    pub async fn select_voicing_confirmed(&self, profile: impl Into<String>) -> Result<()> {
+        let profile = profile.into();
+        let expected = profile.clone();
+        self.confirm(self.select_voicing(profile), move |state| {
+            state.selected_voicing_profile.as_deref() == Some(expected.as_str())
+        })
+        .await
+    }
+
+    /// Select a preset and wait for the change to be reported
+    pub async fn select_preset_confirmed(&self, preset_id: impl Into<String>) -> Result<()> {
+        let preset_id = preset_id.into();
+        let expected = preset_id.clone();
+        self.confirm(self.select_preset(preset_id), move |state| {
+            state.last_selected_preset.as_deref() == Some(expected.as_str())
+        })
+        .await
+    }
+
+    /// Send a request and wait until a room update satisfies `predicate`
+    async fn confirm<F>(&self, send: impl Future<Output = Result<()>>, predicate: F) -> Result<()>
+    where
+        F: Fn(&RoomState) -> bool,
+    {
+        let room_id = self.id();
+
+        // Reported updates are applied to the state from before sending, since
+        // sending sets the requested value locally
+        let mut reported = self.state.load_full();
+
+        // If the value is already in place the speaker won't report a change
+        let already_applied = predicate(&reported);
+
+        // Subscribe before sending so the resulting notify can't be missed
+        let mut rx = self.speaker.connection().subscribe();
+        send.await?;
+
+        if already_applied {
+            return Ok(());
+        }
+
+        let wait = async {
+            loop {
+                match rx.recv().await {
+                    Ok(StateUpdate::RoomUpdate(room_json)) => {
+                        let id = room_json
+                            .get("id")
+                            .and_then(|v| v.as_str())
+                            .and_then(|s| uuid::Uuid::parse_str(s).ok());
+                        if id != Some(room_id) {
+                            continue;
+                        }
+                        let connection = self.speaker.connection();
+                        let room_json = Arc::unwrap_or_clone(room_json);
+                        if let Ok(state) = merge_room_state_from_json(&reported, room_json, connection.parse_context()) {
+                            if predicate(&state) {
+                                return Ok(());
+                            }
+                            reported = Arc::new(state);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => {
+                        return Err(AscendError::ConnectionClosed);
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(CONFIRM_TIMEOUT, wait)
+            .await
+            .map_err(|_| AscendError::Timeout { request: None })?
+    }
+}
+
+/// Cap on how fast a room's gain may rise; see `Room::set_slew_limit`
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::{SlewLimit, SlewMode};
+///
+/// let limit = SlewLimit::reject(6.0);
+/// assert_eq!(limit.mode, SlewMode::Reject);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlewLimit {
+    /// Largest rise in dB per second
+    pub max_rise_per_sec: f64,
+
+    /// What happens to faster rises
+    pub mode: SlewMode,
+}
+
+impl SlewLimit {
+    /// Reject rises faster than `max_rise_per_sec` dB per second
+    pub fn reject(max_rise_per_sec: f64) -> Self {
+        Self {
+            max_rise_per_sec: max_rise_per_sec.max(0.0),
+            mode: SlewMode::Reject,
+        }
+    }
+
+    /// Ramp up at `max_rise_per_sec` dB per second when a rise is faster
+    ///
+    /// With a rate of zero, rises are rejected instead.
+    pub fn smooth(max_rise_per_sec: f64) -> Self {
+        Self {
+            max_rise_per_sec: max_rise_per_sec.max(0.0),
+            mode: SlewMode::Smooth,
+        }
+    }
+}
+
+/// What a `SlewLimit` does with a rise that is too fast
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlewMode {
+    /// Fail with `AscendError::SlewRateExceeded` without changing the gain
+    #[default]
+    Reject,
+
+    /// Raise the gain in steps every 100 ms at the maximum rate; the setter
+    /// returns once the requested gain is sent
+    Smooth,
+}
+
+/// Hardware features of a room, for hiding controls that don't apply
+///
+/// Derived from the member devices' product tags and the room document:
+///
+/// - `has_sub_output`: a member is tagged as a subwoofer
+/// - `has_analog_input`: the room offers an analog XLR input mode
+/// - `supports_streaming`: the room reports a streaming state
+///
+/// Device licenses aren't consulted, as the license names the speakers
+/// report aren't known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoomCapabilities {
+    pub has_sub_output: bool,
+    pub has_analog_input: bool,
+    pub supports_streaming: bool,
+}
+
+impl RoomCapabilities {
+    /// Derive the capabilities of a room from its members
+    pub(crate) fn derive(state: &RoomState, members: &[RoomMember]) -> Self {
+        let devices: Vec<&Device> = members.iter().filter_map(|member| member.device.as_ref()).collect();
+        let has_sub_output = devices.iter().any(|device| device.model() == ProductModel::Subwoofer);
+        let has_analog_input = state
+            .input_modes_raw
+            .iter()
+            .any(|mode| mode == "analogLowGain" || mode == "analogHighGain");
+        let supports_streaming = state.streaming.is_some();
+
+        Self {
+            has_sub_output,
+            has_analog_input,
+            supports_streaming,
+        }
+    }
+}
+
+/// How room documents with unknown or malformed fields are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Log a warning naming the field and carry on with a default value
+    #[default]
+    Lenient,
+
+    /// Reject the document
+    Strict,
+}
+
+/// Room document keys this crate understands
+const KNOWN_ROOM_FIELDS: &[&str] = &[
+    "id",
+    "type",
+    "name",
+    "members",
+    "gain",
+    "mute",
+    "sleep",
+    "selectedInput",
+    "selectedXLR",
+    "inputModes",
+    "selectedVoicingProfile",
+    "voicing",
+    "presets",
+    "lastSelectedPreset",
+    "channelMapping",
+    "streaming",
+    "linearPhase",
+];
+
+/// Parse settings shared by everything parsing documents from one connection
+#[derive(Debug, Default)]
+pub(crate) struct ParseContext {
+    mode: ParseMode,
+    /// Unknown field paths seen so far, for reporting new firmware fields
+    unknown_fields: Mutex<BTreeSet<String>>,
+}
+
+impl ParseContext {
+    pub(crate) fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            unknown_fields: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Get the unknown field paths encountered so far
+    pub(crate) fn unknown_fields(&self) -> Vec<String> {
+        self.unknown_fields.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Report an unknown field, warning the first time it is seen
+    fn unknown_field(&self, path: String) -> Result<()> {
+        if self.mode == ParseMode::Strict {
+            return Err(AscendError::InvalidResponse(format!("Unknown field: {}", path)));
+        }
+        if self.unknown_fields.lock().unwrap().insert(path.clone()) {
+            tracing::warn!("Unknown field in room document: {}", path);
+        }
+        Ok(())
+    }
+
+    /// Parse an optional field, treating absent and `null` as `None`
+    fn optional_field<T: DeserializeOwned>(&self, json: &serde_json::Value, key: &str) -> Result<Option<T>> {
+        match json.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => match serde_json::from_value(value.clone()) {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(e) if self.mode == ParseMode::Strict => {
+                    Err(AscendError::InvalidResponse(format!("Malformed field room.{}: {}", key, e)))
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring malformed field room.{}: {}", key, e);
+                    Ok(None)
+                }
+            },
+        }
+    }
 }
 
 /// Parse room state from JSON value
-fn parse_room_state_from_json(json: serde_json::Value) -> Result<RoomState> {
+pub(crate) fn parse_room_state_from_json(json: serde_json::Value, context: &ParseContext) -> Result<RoomState> {
+    parse_room_state(normalize_room_json(json, context)?, None, context)
+}
+
+/// Apply a room document onto the previous state of the same room
+///
+/// A document carrying `name`, `gain`, and `mute` is complete and replaces the
+/// state, resetting fields it omits. Any other document is partial: its fields
+/// are overlaid onto the previous document and the rest is kept. Either way,
+/// only fields whose JSON differs from the previous document are parsed.
+pub(crate) fn merge_room_state_from_json(
+    previous: &RoomState,
+    json: serde_json::Value,
+    context: &ParseContext,
+) -> Result<RoomState> {
+    let json = normalize_room_json(json, context)?;
+    let update = json
+        .as_object()
+        .ok_or_else(|| AscendError::InvalidResponse("Room document is not an object".to_string()))?;
+
+    if let Some(id) = update.get("id") {
+        let id = id.as_str().and_then(|s| uuid::Uuid::parse_str(s).ok());
+        if id != Some(previous.id) {
+            return Err(AscendError::InvalidResponse(format!(
+                "Room document does not belong to room {}",
+                previous.id
+            )));
+        }
+    }
+
+    let complete = ["name", "gain", "mute"].iter().all(|key| update.contains_key(*key));
+    let merged = if complete {
+        json
+    } else {
+        let mut merged = previous.raw_json.as_ref().clone();
+        if let Some(merged) = merged.as_object_mut() {
+            for (key, value) in update {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        merged
+    };
+
+    parse_room_state(merged, Some(previous), context)
+}
+
+/// Split input modes into regular and XLR modes
+fn split_input_modes(input_modes_raw: &[String]) -> (Vec<String>, Vec<String>) {
+    let xlr_mode_names = ["aes", "analogLowGain", "analogHighGain"];
+    let mut input_modes = Vec::new();
+    let mut xlr_input_modes = Vec::new();
+
+    for mode in input_modes_raw {
+        if xlr_mode_names.contains(&mode.as_str()) {
+            xlr_input_modes.push(mode.clone());
+        } else {
+            input_modes.push(mode.clone());
+        }
+    }
+    (input_modes, xlr_input_modes)
+}
+
+/// Write a field of the parsed state into its room document
+///
+/// Only fields the setters change are written; gain limits, per-position mute
+/// states, and voicing EQ tables keep their reported values.
+fn patch_room_json(json: &mut serde_json::Value, state: &RoomState, field: RoomField) {
+    let Some(obj) = json.as_object_mut() else {
+        return;
+    };
+    let (key, value) = match field {
+        RoomField::Name => ("name", serde_json::json!(state.name)),
+        RoomField::Gain => {
+            match obj.get_mut("gain").and_then(|gain| gain.as_object_mut()) {
+                Some(gain) => {
+                    gain.insert("global".to_string(), serde_json::json!(state.gain.global));
+                }
+                None => {
+                    obj.insert("gain".to_string(), serde_json::json!(state.gain));
+                }
+            }
+            return;
+        }
+        RoomField::Mute => ("mute", serde_json::json!(state.mute)),
+        RoomField::Sleep => ("sleep", serde_json::json!(state.sleep)),
+        RoomField::SelectedInput => ("selectedInput", serde_json::json!(state.selected_input)),
+        RoomField::SelectedXlr => ("selectedXLR", serde_json::json!(state.selected_xlr)),
+        RoomField::SelectedVoicingProfile => {
+            ("selectedVoicingProfile", serde_json::json!(state.selected_voicing_profile))
+        }
+        RoomField::Voicing => {
+            let Some(profiles) = obj.get_mut("voicing").and_then(|voicing| voicing.as_object_mut()) else {
+                return;
+            };
+            for (id, profile) in state.voicing.iter() {
+                if let Some(raw) = profiles.get_mut(id).and_then(|raw| raw.as_object_mut()) {
+                    raw.insert("sub".to_string(), serde_json::json!(profile.sub));
+                    raw.insert("bass".to_string(), serde_json::json!(profile.bass));
+                    raw.insert("treble".to_string(), serde_json::json!(profile.treble));
+                }
+            }
+            return;
+        }
+        RoomField::LastSelectedPreset => ("lastSelectedPreset", serde_json::json!(state.last_selected_preset)),
+        RoomField::LinearPhase => ("linearPhase", serde_json::json!(state.linear_phase)),
+        RoomField::Members
+        | RoomField::InputModes
+        | RoomField::Presets
+        | RoomField::ChannelMapping
+        | RoomField::Streaming => return,
+    };
+    obj.insert(key.to_string(), value);
+}
+
+/// Work around firmware quirks and report unknown fields
+fn normalize_room_json(json: serde_json::Value, context: &ParseContext) -> Result<serde_json::Value> {
     // API bug workaround: Replace "AES Streamer" with "XLR"
     let mut json = json;
     if let Some(obj) = json.as_object_mut() {
@@ -456,7 +1956,54 @@ fn parse_room_state_from_json(json: serde_json::Value) -> Result<RoomState> {
                 obj.insert("selectedInput".to_string(), serde_json::Value::String("XLR".to_string()));
             }
         }
+
+        for key in obj.keys() {
+            if !KNOWN_ROOM_FIELDS.contains(&key.as_str()) {
+                context.unknown_field(format!("room.{}", key))?;
+            }
+        }
     }
+    Ok(json)
+}
+
+/// Parse the voicing profiles, leaving their EQ settings in the document
+fn parse_voicing(json: &Arc<serde_json::Value>, context: &ParseContext) -> Result<BTreeMap<String, VoicingProfile>> {
+    // Parse a copy without the EQ tables, which are the bulk of the field
+    let stripped = match json.get("voicing") {
+        Some(serde_json::Value::Object(profiles)) => {
+            let profiles = profiles
+                .iter()
+                .map(|(id, profile)| {
+                    let profile = match profile {
+                        serde_json::Value::Object(fields) => fields
+                            .iter()
+                            .filter(|(key, _)| key.as_str() != "paramEQ")
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect(),
+                        other => other.clone(),
+                    };
+                    (id.clone(), profile)
+                })
+                .collect();
+            serde_json::json!({ "voicing": serde_json::Value::Object(profiles) })
+        }
+        Some(other) => serde_json::json!({ "voicing": other }),
+        None => return Ok(BTreeMap::new()),
+    };
+
+    let mut voicing: BTreeMap<String, VoicingProfile> = context.optional_field(&stripped, "voicing")?.unwrap_or_default();
+    for (id, profile) in &mut voicing {
+        let pointer = format!("/voicing/{}/paramEQ", id.replace('~', "~0").replace('/', "~1"));
+        profile.param_eq = ParamEq::in_document(json.clone(), pointer);
+    }
+    Ok(voicing)
+}
+
+/// Parse a normalized room document, reusing unchanged fields of `previous`
+fn parse_room_state(json: serde_json::Value, previous: Option<&RoomState>, context: &ParseContext) -> Result<RoomState> {
+    let json = Arc::new(json);
+    // The previous state, if the field's JSON is identical to its document
+    let unchanged = |key: &str| previous.filter(|previous| previous.raw_json.get(key) == json.get(key));
 
     let id: RoomId = json.get("id")
         .and_then(|v| v.as_str())
@@ -468,72 +2015,66 @@ fn parse_room_state_from_json(json: serde_json::Value) -> Result<RoomState> {
         .map(|s| s.to_string())
         .ok_or_else(|| AscendError::InvalidResponse("Missing room name".to_string()))?;
 
-    let members: BTreeMap<DeviceId, String> = json.get("members")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-
-    let gain: GainData = json.get("gain")
-        .ok_or_else(|| AscendError::InvalidResponse("Missing gain data".to_string()))
-        .and_then(|v| serde_json::from_value(v.clone()).map_err(AscendError::Json))?;
-
-    let mute: MuteData = json.get("mute")
-        .ok_or_else(|| AscendError::InvalidResponse("Missing mute data".to_string()))
-        .and_then(|v| serde_json::from_value(v.clone()).map_err(AscendError::Json))?;
-
-    let sleep: bool = json.get("sleep")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
-
-    let selected_input: Option<String> = json.get("selectedInput")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
-
-    let selected_xlr: Option<String> = json.get("selectedXLR")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
-
-    let input_modes_raw: Vec<String> = json.get("inputModes")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-
-    // Split input modes into regular and XLR
-    let xlr_mode_names = ["aes", "analogLowGain", "analogHighGain"];
-    let mut input_modes = Vec::new();
-    let mut xlr_input_modes = Vec::new();
-
-    for mode in &input_modes_raw {
-        if xlr_mode_names.contains(&mode.as_str()) {
-            xlr_input_modes.push(mode.clone());
-        } else {
-            input_modes.push(mode.clone());
+    let members = match unchanged("members") {
+        Some(previous) => previous.members.clone(),
+        None => Arc::new(context.optional_field(&json, "members")?.unwrap_or_default()),
+    };
+
+    let gain: GainData = match unchanged("gain") {
+        Some(previous) => previous.gain.clone(),
+        None => json.get("gain")
+            .ok_or_else(|| AscendError::InvalidResponse("Missing gain data".to_string()))
+            .and_then(|v| serde_json::from_value(v.clone()).map_err(AscendError::Json))?,
+    };
+
+    let mute: MuteData = match unchanged("mute") {
+        Some(previous) => previous.mute.clone(),
+        None => json.get("mute")
+            .ok_or_else(|| AscendError::InvalidResponse("Missing mute data".to_string()))
+            .and_then(|v| serde_json::from_value(v.clone()).map_err(AscendError::Json))?,
+    };
+
+    let sleep: bool = context.optional_field(&json, "sleep")?.unwrap_or(false);
+
+    let selected_input: Option<String> = context.optional_field(&json, "selectedInput")?;
+
+    let selected_xlr: Option<String> = context.optional_field(&json, "selectedXLR")?;
+
+    let (input_modes_raw, input_modes, xlr_input_modes) = match unchanged("inputModes") {
+        Some(previous) => (
+            previous.input_modes_raw.clone(),
+            previous.input_modes.clone(),
+            previous.xlr_input_modes.clone(),
+        ),
+        None => {
+            let input_modes_raw: Vec<String> = context.optional_field(&json, "inputModes")?.unwrap_or_default();
+            let (input_modes, xlr_input_modes) = split_input_modes(&input_modes_raw);
+            (Arc::new(input_modes_raw), Arc::new(input_modes), Arc::new(xlr_input_modes))
         }
-    }
+    };
 
-    let selected_voicing_profile: Option<String> = json.get("selectedVoicingProfile")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
+    let selected_voicing_profile: Option<String> = context.optional_field(&json, "selectedVoicingProfile")?;
 
-    let voicing: BTreeMap<String, VoicingProfile> = json.get("voicing")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+    let voicing = match unchanged("voicing") {
+        Some(previous) => previous.voicing.clone(),
+        None => Arc::new(parse_voicing(&json, context)?),
+    };
 
-    let presets: BTreeMap<String, Preset> = json.get("presets")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+    let presets = match unchanged("presets") {
+        Some(previous) => previous.presets.clone(),
+        None => Arc::new(context.optional_field(&json, "presets")?.unwrap_or_default()),
+    };
 
-    let last_selected_preset: Option<String> = json.get("lastSelectedPreset")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
+    let last_selected_preset: Option<String> = context.optional_field(&json, "lastSelectedPreset")?;
 
-    let channel_mapping: Option<ChannelMapping> = json.get("channelMapping")
-        .and_then(|v| serde_json::from_value(v.clone()).ok());
+    let channel_mapping = match unchanged("channelMapping") {
+        Some(previous) => previous.channel_mapping.clone(),
+        None => context.optional_field::<ChannelMapping>(&json, "channelMapping")?.map(Arc::new),
+    };
 
-    let streaming: Option<bool> = json.get("streaming")
-        .and_then(|v| v.as_bool());
+    let streaming: Option<bool> = context.optional_field(&json, "streaming")?;
 
-    let linear_phase: bool = json.get("linearPhase")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
+    let linear_phase: bool = context.optional_field(&json, "linearPhase")?.unwrap_or(false);
 
     Ok(RoomState {
         id,
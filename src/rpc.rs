@@ -0,0 +1,378 @@
+//! JSON-RPC 2.0 server exposing rooms to other tools (requires the `rpc` feature)
+//!
+//! Messages are newline-delimited JSON, over TCP or stdio. Methods mirror the
+//! [`Room`] API and take the room as a `room` parameter, given by ID or name;
+//! it may be omitted when the speaker serves a single room.
+//!
+//! | Method | Parameters | Result |
+//! |--------|------------|--------|
+//! | `rooms` | | `[{"id", "name"}]` |
+//! | `room.state` | `room` | room document |
+//! | `room.setGain` | `room`, `gain` | `null` |
+//! | `room.setMute` | `room`, `mute` | `null` |
+//! | `room.setStandby` | `room`, `standby` | `null` |
+//! | `room.setInput` | `room`, `input` | `null` |
+//! | `room.setXlrMode` | `room`, `mode` | `null` |
+//! | `room.setLinearPhase` | `room`, `enabled` | `null` |
+//! | `room.selectVoicing` | `room`, `profile` | `null` |
+//! | `room.selectPreset` | `room`, `preset` | `null` |
+//! | `room.updateTone` | `room`, `tone` (`{"sub", "mid", "treble"}`) | `null` |
+//! | `room.undo` | `room` | whether anything was undone |
+//! | `subscribe`, `unsubscribe` | | `null` |
+//!
+//! After `subscribe`, the connection receives a `roomChanged` notification
+//! with `{"room", "state"}` params whenever a room's state changes.
+//! `subscribe` and `unsubscribe` apply to the connection, so they must be sent
+//! on their own; inside a batch they fail with an invalid request error. The
+//! other entries of a batch run one after another, in order.
+//!
+//! Each connection handles up to 16 requests at a time; further lines are not
+//! read until one finishes.
+//!
+//! ```text
+//! --> {"jsonrpc": "2.0", "id": 1, "method": "room.setGain", "params": {"room": "Studio", "gain": -25.0}}
+//! <-- {"jsonrpc": "2.0", "id": 1, "result": null}
+//! ```
+
+use crate::client::AscendClient;
+use crate::error::{AscendError, Result};
+use crate::room::Room;
+use crate::subscription::StateUpdate;
+use crate::types::{GainDb, MuteState, RoomId, ToneSettings};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::{mpsc, Semaphore};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Any error reported by the speaker or connection
+const SERVER_ERROR: i64 = -32000;
+
+/// Requests handled at once per connection
+const MAX_IN_FLIGHT: usize = 16;
+/// Replies and notifications buffered per connection before senders wait
+const OUTGOING_CAPACITY: usize = 64;
+
+/// Serves JSON-RPC connections for the rooms of one client
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::rpc::RpcServer;
+/// use dutchdutch_ascend::AscendClient;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///     RpcServer::new(client).serve_tcp("127.0.0.1:4000").await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RpcServer {
+    client: Arc<AscendClient>,
+}
+
+/// A JSON-RPC error
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<AscendError> for RpcError {
+    fn from(error: AscendError) -> Self {
+        let code = match error {
+            AscendError::InvalidArgument(_) | AscendError::RoomNotFound(_) | AscendError::AmbiguousRoom(_) => {
+                INVALID_PARAMS
+            }
+            _ => SERVER_ERROR,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+type RpcResult = std::result::Result<Value, RpcError>;
+
+impl RpcServer {
+    /// Create a server for the rooms of a client
+    pub fn new(client: AscendClient) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+
+    /// Accept TCP connections and serve each one until it closes
+    ///
+    /// Only returns if the listener fails.
+    pub async fn serve_tcp(&self, addr: impl ToSocketAddrs) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!("JSON-RPC server listening on {}", listener.local_addr()?);
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            tracing::debug!("JSON-RPC connection from {}", peer);
+            let server = self.clone();
+            tokio::spawn(async move {
+                let (reader, writer) = stream.into_split();
+                if let Err(e) = server.serve(reader, writer).await {
+                    tracing::debug!("JSON-RPC connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    /// Serve requests from stdin, writing responses to stdout
+    pub async fn serve_stdio(&self) -> Result<()> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serve one connection until the reader reaches end of file
+    ///
+    /// Requests are handled concurrently, up to 16 at a time, so responses may
+    /// arrive out of order; match them by `id`. Once the limit is reached, the
+    /// next line is only read when a request finishes.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (out_tx, mut out_rx) = mpsc::channel::<String>(OUTGOING_CAPACITY);
+        let in_flight = Arc::new(Semaphore::new(MAX_IN_FLIGHT));
+        let writer_task = tokio::spawn(async move {
+            while let Some(mut line) = out_rx.recv().await {
+                line.push('\n');
+                if writer.write_all(line.as_bytes()).await.is_err() || writer.flush().await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut notifications: Option<tokio::task::JoinHandle<()>> = None;
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Subscriptions belong to the connection, so they are handled here
+            let message = serde_json::from_str::<Value>(&line);
+            if let Ok(message) = &message {
+                if let Some(subscribe) = subscription_request(message) {
+                    let result = if !subscribe {
+                        if let Some(task) = notifications.take() {
+                            task.abort();
+                        }
+                        Ok(())
+                    } else if notifications.is_none() {
+                        match self.notify(out_tx.clone()).await {
+                            Ok(task) => {
+                                notifications = Some(task);
+                                Ok(())
+                            }
+                            Err(e) => Err(RpcError::from(e)),
+                        }
+                    } else {
+                        Ok(())
+                    };
+                    if let Some(id) = message.get("id") {
+                        let reply = response(id.clone(), result.map(|()| Value::Null));
+                        let _ = out_tx.send(reply.to_string()).await;
+                    }
+                    continue;
+                }
+            }
+
+            let Ok(permit) = in_flight.clone().acquire_owned().await else {
+                break;
+            };
+            let server = self.clone();
+            let out_tx = out_tx.clone();
+            tokio::spawn(async move {
+                let reply = match message {
+                    Ok(message) => server.handle_message(message).await,
+                    Err(e) => Some(response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))),
+                };
+                if let Some(reply) = reply {
+                    let _ = out_tx.send(reply.to_string()).await;
+                }
+                drop(permit);
+            });
+        }
+
+        if let Some(task) = notifications {
+            task.abort();
+        }
+        drop(out_tx);
+        let _ = writer_task.await;
+        Ok(())
+    }
+
+    /// Forward room changes to a connection as `roomChanged` notifications
+    async fn notify(&self, out_tx: mpsc::Sender<String>) -> Result<tokio::task::JoinHandle<()>> {
+        let mut updates = self.client.subscribe_state().await?;
+        let client = self.client.clone();
+        Ok(tokio::spawn(async move {
+            loop {
+                let room_id = match updates.recv().await {
+                    Ok(StateUpdate::RoomChanged(delta)) => delta.room_id,
+                    Ok(_) => continue,
+                    Err(AscendError::ConnectionClosed) => break,
+                    Err(e) => {
+                        tracing::debug!("JSON-RPC notifications missed updates: {}", e);
+                        continue;
+                    }
+                };
+                let Ok(room) = client.room_by_id(room_id).await else {
+                    continue;
+                };
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "roomChanged",
+                    "params": { "room": room_id, "state": room.raw_json() },
+                });
+                if out_tx.send(notification.to_string()).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Handle a request, notification, or batch, returning the reply if any
+    async fn handle_message(&self, message: Value) -> Option<Value> {
+        let Value::Array(batch) = message else {
+            return self.handle_request(message).await;
+        };
+        if batch.is_empty() {
+            return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Empty batch"))));
+        }
+
+        let mut replies = Vec::with_capacity(batch.len());
+        for request in batch {
+            if let Some(reply) = self.handle_request(request).await {
+                replies.push(reply);
+            }
+        }
+        (!replies.is_empty()).then_some(Value::Array(replies))
+    }
+
+    /// Handle a single request; notifications (without `id`) get no reply
+    async fn handle_request(&self, request: Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let result = match (request.get("jsonrpc"), request.get("method").and_then(Value::as_str)) {
+            (Some(version), Some(method)) if version == "2.0" => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.call(method, params).await
+            }
+            _ => {
+                return Some(response(
+                    id.unwrap_or(Value::Null),
+                    Err(RpcError::new(INVALID_REQUEST, "Not a JSON-RPC 2.0 request")),
+                ))
+            }
+        };
+
+        if let Err(e) = &result {
+            tracing::debug!("JSON-RPC request failed: {}", e.message);
+        }
+        id.map(|id| response(id, result))
+    }
+
+    /// Run a method
+    async fn call(&self, method: &str, params: Value) -> RpcResult {
+        if method == "subscribe" || method == "unsubscribe" {
+            return Err(RpcError::new(INVALID_REQUEST, format!("{} can't be sent in a batch", method)));
+        }
+        if method == "rooms" {
+            let rooms = self.client.rooms().await?;
+            return Ok(rooms
+                .iter()
+                .map(|room| json!({ "id": room.id(), "name": room.name() }))
+                .collect());
+        }
+
+        let Some(method) = method.strip_prefix("room.") else {
+            return Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method)));
+        };
+        let room = self.room(&params).await?;
+
+        match method {
+            "state" => return Ok(room.raw_json()),
+            "undo" => return Ok(Value::Bool(room.undo().await?)),
+            "setGain" => room.set_gain(param::<GainDb>(&params, "gain")?).await?,
+            "setMute" => room.set_mute(param::<MuteState>(&params, "mute")?).await?,
+            "setStandby" => room.set_standby(param(&params, "standby")?).await?,
+            "setInput" => room.set_input(param::<String>(&params, "input")?).await?,
+            "setXlrMode" => room.set_xlr_mode(param::<String>(&params, "mode")?).await?,
+            "setLinearPhase" => room.set_linear_phase(param(&params, "enabled")?).await?,
+            "selectVoicing" => room.select_voicing(param::<String>(&params, "profile")?).await?,
+            "selectPreset" => room.select_preset(param::<String>(&params, "preset")?).await?,
+            "updateTone" => room.update_tone(param::<ToneSettings>(&params, "tone")?).await?,
+            _ => {
+                return Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("Unknown method: room.{}", method),
+                ))
+            }
+        }
+        Ok(Value::Null)
+    }
+
+    /// Resolve the `room` parameter by ID or name, or the only room if absent
+    async fn room(&self, params: &Value) -> std::result::Result<Room, RpcError> {
+        let room = match params.get("room") {
+            None | Some(Value::Null) => self.client.room().await?,
+            Some(Value::String(room)) => match room.parse::<RoomId>() {
+                Ok(room_id) => self.client.room_by_id(room_id).await?,
+                Err(_) => self.client.room_by_name(room).await?,
+            },
+            Some(_) => return Err(RpcError::new(INVALID_PARAMS, "room must be a string")),
+        };
+        Ok(room)
+    }
+}
+
+/// Check for `subscribe` (`Some(true)`) or `unsubscribe` (`Some(false)`)
+fn subscription_request(message: &Value) -> Option<bool> {
+    if message.get("jsonrpc")? != "2.0" {
+        return None;
+    }
+    match message.get("method")?.as_str()? {
+        "subscribe" => Some(true),
+        "unsubscribe" => Some(false),
+        _ => None,
+    }
+}
+
+/// Read a required named parameter
+fn param<T: DeserializeOwned>(params: &Value, name: &str) -> std::result::Result<T, RpcError> {
+    let value = params
+        .get(name)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing parameter: {}", name)))?;
+    T::deserialize(value).map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid parameter {}: {}", name, e)))
+}
+
+/// Build a response object
+fn response(id: Value, result: RpcResult) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
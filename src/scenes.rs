@@ -0,0 +1,212 @@
+use crate::error::{AscendError, Result};
+use crate::room::Room;
+use crate::types::{GainDb, MuteState, RoomId};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings a scene applies to a single room
+///
+/// Fields left as `None` are not touched when the scene is applied.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoomScene {
+    /// Preset to select (applied first, since it may change other settings)
+    #[serde(default)]
+    pub preset: Option<String>,
+
+    /// Voicing profile to select
+    #[serde(default)]
+    pub voicing: Option<String>,
+
+    /// Input source to select
+    #[serde(default)]
+    pub input: Option<String>,
+
+    /// Global gain in dB
+    #[serde(default)]
+    pub gain: Option<GainDb>,
+
+    /// Global mute state
+    #[serde(default)]
+    pub mute: Option<MuteState>,
+}
+
+impl RoomScene {
+    /// Capture the current settings of a room
+    ///
+    /// The preset is the one last selected, if the speaker reports it;
+    /// applying the capture selects it again before the other settings.
+    pub fn capture(room: &Room) -> Self {
+        let state = room.state_snapshot();
+        Self {
+            preset: state.last_selected_preset.clone(),
+            voicing: state.selected_voicing_profile.clone(),
+            input: state.selected_input.clone(),
+            gain: Some(state.gain.global),
+            mute: Some(state.mute.global),
+        }
+    }
+}
+
+/// A named configuration spanning one or more rooms
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::{GainDb, RoomScene, Scene};
+///
+/// let room_id = uuid::Uuid::new_v4();
+/// let scene = Scene::new("Movie night").with_room(
+///     room_id,
+///     RoomScene {
+///         input: Some("XLR".to_string()),
+///         gain: Some(GainDb::new(-25.0)),
+///         mute: Some(false),
+///         ..Default::default()
+///     },
+/// );
+/// assert_eq!(scene.rooms.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+
+    /// Per-room settings
+    #[serde(default)]
+    pub rooms: BTreeMap<RoomId, RoomScene>,
+}
+
+impl Scene {
+    /// Create an empty scene
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            rooms: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace the settings for a room
+    pub fn with_room(mut self, room_id: RoomId, settings: RoomScene) -> Self {
+        self.rooms.insert(room_id, settings);
+        self
+    }
+
+    /// Capture the current settings of the given rooms as a scene
+    pub fn capture<'a>(name: impl Into<String>, rooms: impl IntoIterator<Item = &'a Room>) -> Self {
+        let mut scene = Self::new(name);
+        for room in rooms {
+            scene.rooms.insert(room.id(), RoomScene::capture(room));
+        }
+        scene
+    }
+}
+
+/// Applies scenes to a set of rooms
+///
+/// Application is all-or-nothing on a best-effort basis: every room referenced
+/// by the scene must be known before anything is sent, and if any room fails,
+/// the rooms are restored to the preset, voicing, input, gain, and mute
+/// captured just before applying. Settings outside a [`RoomScene`], such as
+/// tone adjustments made since the previous preset was selected, are not
+/// restored.
+pub struct SceneRunner {
+    rooms: BTreeMap<RoomId, Room>,
+}
+
+impl SceneRunner {
+    /// Create a runner over the given rooms
+    pub fn new(rooms: impl IntoIterator<Item = Room>) -> Self {
+        Self {
+            rooms: rooms.into_iter().map(|room| (room.id(), room)).collect(),
+        }
+    }
+
+    /// Apply a scene to its rooms concurrently
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dutchdutch_ascend::{AscendClient, GainDb, RoomScene, Scene, SceneRunner};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    /// let rooms = client.rooms().await?;
+    /// let scene = Scene::new("Mixing").with_room(
+    ///     rooms[0].id(),
+    ///     RoomScene {
+    ///         voicing: Some("Neutral".to_string()),
+    ///         gain: Some(GainDb::new(-30.0)),
+    ///         ..Default::default()
+    ///     },
+    /// );
+    ///
+    /// SceneRunner::new(rooms).apply(&scene).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply(&self, scene: &Scene) -> Result<()> {
+        // Resolve every room before sending anything
+        let mut targets = Vec::with_capacity(scene.rooms.len());
+        for (room_id, settings) in &scene.rooms {
+            let room = self
+                .rooms
+                .get(room_id)
+                .ok_or_else(|| AscendError::RoomNotFound(room_id.to_string()))?;
+            targets.push((room, settings));
+        }
+
+        // Only reselect the previous preset if the scene changes it, since
+        // selecting it again resets any later changes made on top of it
+        let previous: Vec<_> = targets
+            .iter()
+            .map(|(room, settings)| {
+                let mut captured = RoomScene::capture(room);
+                if settings.preset.is_none() {
+                    captured.preset = None;
+                }
+                (*room, captured)
+            })
+            .collect();
+
+        tracing::info!("Applying scene '{}' to {} room(s)", scene.name, targets.len());
+
+        let results = join_all(targets.iter().map(|(room, settings)| apply_room(room, settings))).await;
+        let Some(error) = results.into_iter().find_map(|r| r.err()) else {
+            return Ok(());
+        };
+
+        tracing::warn!("Scene '{}' failed ({}), restoring previous settings", scene.name, error);
+        for result in join_all(previous.iter().map(|(room, settings)| apply_room(room, settings))).await {
+            if let Err(e) = result {
+                tracing::warn!("Failed to restore room settings: {}", e);
+            }
+        }
+
+        Err(error)
+    }
+}
+
+/// Apply scene settings to a single room
+///
+/// The preset is applied first, since it may change other settings; the rest
+/// is sent as one batch, failing with the first error if any change fails.
+async fn apply_room(room: &Room, settings: &RoomScene) -> Result<()> {
+    if let Some(preset) = &settings.preset {
+        room.select_preset(preset.as_str()).await?;
+    }
+
+    let mut batch = room.batch();
+    if let Some(voicing) = &settings.voicing {
+        batch = batch.voicing(voicing.as_str());
+    }
+    if let Some(input) = &settings.input {
+        batch = batch.input(input.as_str());
+    }
+    if let Some(gain) = settings.gain {
+        batch = batch.gain(gain);
+    }
+    if let Some(mute) = settings.mute {
+        batch = batch.mute(mute);
+    }
+    batch.send().await.into_iter().collect()
+}
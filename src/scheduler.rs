@@ -0,0 +1,492 @@
+//! Cron-like scheduling of room actions (requires the `scheduler` feature)
+
+use crate::error::{AscendError, Result};
+use crate::room::Room;
+use crate::types::{GainDb, RoomId};
+use chrono::{Datelike, Local, NaiveDateTime, TimeDelta, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Longest gap in minutes, e.g. after the clock jumped or the host slept, whose
+/// missed minutes are still run
+const MAX_CATCH_UP_MINUTES: i64 = 90;
+
+/// Parsed five-field cron expression (`minute hour day-of-month month day-of-week`)
+///
+/// Each field accepts `*`, single values, comma-separated lists, ranges (`1-5`),
+/// and steps (`*/15`, `0-30/10`). Day-of-week uses 0 (or 7) for Sunday. As in
+/// standard cron, when both day-of-month and day-of-week are restricted (not
+/// starting with `*`), a day matching either one fires, so `0 0 1 * 1` runs on
+/// the 1st and on every Monday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    /// Both day fields are restricted, so a day matching either fires
+    either_day: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::scheduler::CronSchedule;
+    ///
+    /// // Every day at midnight
+    /// assert!(CronSchedule::parse("0 0 * * *").is_ok());
+    /// // Every 15 minutes between 22:00 and 23:59
+    /// assert!(CronSchedule::parse("*/15 22-23 * * *").is_ok());
+    /// assert!(CronSchedule::parse("61 * * * *").is_err());
+    /// ```
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(AscendError::InvalidArgument(format!(
+                "Cron expression must have 5 fields: {}",
+                expr
+            )));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // Normalize Sunday as 0
+        for day in days_of_week.iter_mut() {
+            if *day == 7 {
+                *day = 0;
+            }
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            either_day: !fields[2].starts_with('*') && !fields[4].starts_with('*'),
+        })
+    }
+
+    /// Check if the schedule fires at the given time (minute resolution)
+    ///
+    /// Accepts any date and time, e.g. a `DateTime<Local>` or a
+    /// `NaiveDateTime` of local wall-clock time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Local, TimeZone};
+    /// use dutchdutch_ascend::scheduler::CronSchedule;
+    ///
+    /// // The 1st of the month and every Monday
+    /// let schedule = CronSchedule::parse("0 0 1 * 1").unwrap();
+    /// assert!(schedule.matches(&Local.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()));
+    /// assert!(schedule.matches(&Local.with_ymd_and_hms(2026, 10, 5, 0, 0, 0).unwrap()));
+    /// assert!(!schedule.matches(&Local.with_ymd_and_hms(2026, 10, 2, 0, 0, 0).unwrap()));
+    /// ```
+    pub fn matches<T: Datelike + Timelike>(&self, time: &T) -> bool {
+        let day_of_month = self.days_of_month.contains(&time.day());
+        let day_of_week = self.days_of_week.contains(&time.weekday().num_days_from_sunday());
+        let day = if self.either_day {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        };
+
+        self.minutes.contains(&time.minute())
+            && self.hours.contains(&time.hour())
+            && self.months.contains(&time.month())
+            && day
+    }
+}
+
+/// Parse a single cron field into the list of values it matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>> {
+    let invalid = || AscendError::InvalidArgument(format!("Invalid cron field: {}", field));
+
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                start.parse().map_err(|_| invalid())?,
+                end.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse().map_err(|_| invalid())?;
+            (value, value)
+        };
+
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+/// Action executed against a room when a schedule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum ScheduledAction {
+    /// Enter or leave standby
+    SetStandby { standby: bool },
+    /// Set the global gain in dB
+    SetGain { gain: GainDb },
+    /// Lower the global gain to `max` if it is currently above it
+    CapGain { max: GainDb },
+    /// Set the global mute state
+    SetMute { mute: bool },
+    /// Select an input source
+    SelectInput { input: String },
+    /// Select a preset
+    SelectPreset { preset: String },
+}
+
+impl ScheduledAction {
+    /// Execute the action against a room
+    pub async fn execute(&self, room: &Room) -> Result<()> {
+        match self {
+            Self::SetStandby { standby } => room.set_standby(*standby).await,
+            Self::SetGain { gain } => room.set_gain(*gain).await,
+            Self::CapGain { max } => {
+                if room.gain().global > *max {
+                    room.set_gain(*max).await
+                } else {
+                    Ok(())
+                }
+            }
+            Self::SetMute { mute } => room.set_mute(*mute).await,
+            Self::SelectInput { input } => room.set_input(input.as_str()).await,
+            Self::SelectPreset { preset } => room.select_preset(preset.as_str()).await,
+        }
+    }
+}
+
+/// A scheduled action for a room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleEntry {
+    /// Caller-chosen unique identifier
+    pub id: String,
+
+    /// Cron expression, see [`CronSchedule`]
+    pub cron: String,
+
+    /// Room the action applies to
+    pub room_id: RoomId,
+
+    /// Action to execute
+    #[serde(flatten)]
+    pub action: ScheduledAction,
+
+    /// Disabled entries are kept but never fire
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Persistence backend for schedule entries
+///
+/// Implement this to keep schedules in a file, database, or settings service.
+pub trait ScheduleStore: Send + Sync {
+    /// Load all stored entries
+    fn load(&self) -> Result<Vec<ScheduleEntry>>;
+
+    /// Replace the stored entries
+    fn save(&self, entries: &[ScheduleEntry]) -> Result<()>;
+}
+
+/// In-memory store that does not persist across restarts
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<Vec<ScheduleEntry>>,
+}
+
+impl ScheduleStore for MemoryStore {
+    fn load(&self) -> Result<Vec<ScheduleEntry>> {
+        Ok(self.entries.lock().unwrap().clone())
+    }
+
+    fn save(&self, entries: &[ScheduleEntry]) -> Result<()> {
+        *self.entries.lock().unwrap() = entries.to_vec();
+        Ok(())
+    }
+}
+
+/// Runs scheduled actions against `Room` handles
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::scheduler::{MemoryStore, ScheduleEntry, ScheduledAction, Scheduler};
+/// use dutchdutch_ascend::AscendClient;
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///     let rooms = client.rooms().await?;
+///     let room_id = rooms[0].id();
+///
+///     let mut scheduler = Scheduler::new(rooms, Arc::new(MemoryStore::default()))?;
+///     scheduler.add(ScheduleEntry {
+///         id: "midnight-standby".to_string(),
+///         cron: "0 0 * * *".to_string(),
+///         room_id,
+///         action: ScheduledAction::SetStandby { standby: true },
+///         enabled: true,
+///     })?;
+///     scheduler.start();
+///
+///     tokio::signal::ctrl_c().await?;
+///     scheduler.stop();
+///     Ok(())
+/// }
+/// ```
+pub struct Scheduler {
+    rooms: Arc<BTreeMap<RoomId, Room>>,
+    entries: Arc<Mutex<Vec<(ScheduleEntry, CronSchedule)>>>,
+    store: Arc<dyn ScheduleStore>,
+    /// Held across changing and saving the entries, so saves happen in order
+    save_lock: Mutex<()>,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Scheduler {
+    /// Create a scheduler over the given rooms, loading entries from the store
+    pub fn new(rooms: impl IntoIterator<Item = Room>, store: Arc<dyn ScheduleStore>) -> Result<Self> {
+        let entries = store
+            .load()?
+            .into_iter()
+            .map(|entry| {
+                let cron = CronSchedule::parse(&entry.cron)?;
+                Ok((entry, cron))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            rooms: Arc::new(rooms.into_iter().map(|room| (room.id(), room)).collect()),
+            entries: Arc::new(Mutex::new(entries)),
+            store,
+            save_lock: Mutex::new(()),
+            task_handle: None,
+        })
+    }
+
+    /// Get all schedule entries
+    pub fn entries(&self) -> Vec<ScheduleEntry> {
+        self.entries.lock().unwrap().iter().map(|(entry, _)| entry.clone()).collect()
+    }
+
+    /// Add or replace (by id) a schedule entry and persist the change
+    pub fn add(&self, entry: ScheduleEntry) -> Result<()> {
+        let cron = CronSchedule::parse(&entry.cron)?;
+        if !self.rooms.contains_key(&entry.room_id) {
+            return Err(AscendError::RoomNotFound(entry.room_id.to_string()));
+        }
+
+        let _saving = self.save_lock.lock().unwrap();
+        let snapshot = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|(existing, _)| existing.id != entry.id);
+            entries.push((entry, cron));
+            snapshot(&entries)
+        };
+        self.store.save(&snapshot)
+    }
+
+    /// Remove a schedule entry by id and persist the change
+    ///
+    /// Returns whether an entry was removed.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let _saving = self.save_lock.lock().unwrap();
+        let snapshot = {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|(entry, _)| entry.id != id);
+            if entries.len() == before {
+                return Ok(false);
+            }
+            snapshot(&entries)
+        };
+        self.store.save(&snapshot)?;
+        Ok(true)
+    }
+
+    /// Start executing schedules in the background
+    ///
+    /// Every local wall-clock minute is evaluated once, in order. Minutes
+    /// missed because the task fell behind, or skipped when daylight saving
+    /// time starts, are caught up on, up to 90 minutes back; a minute that
+    /// repeats when daylight saving time ends is not run again. Due actions
+    /// run in their own tasks, so a slow one doesn't delay the others.
+    ///
+    /// If the scheduler is already running, it will be stopped and restarted.
+    pub fn start(&mut self) {
+        self.stop();
+
+        let rooms = self.rooms.clone();
+        let entries = self.entries.clone();
+
+        self.task_handle = Some(tokio::spawn(async move {
+            let mut last_evaluated = current_minute();
+            loop {
+                // Sleep until the start of the next minute
+                let now = Local::now();
+                let into_minute = Duration::from_secs(now.second() as u64)
+                    + Duration::from_nanos(now.nanosecond().min(999_999_999) as u64);
+                tokio::time::sleep(Duration::from_secs(60).saturating_sub(into_minute)).await;
+
+                let now = current_minute();
+                if now - last_evaluated > TimeDelta::minutes(MAX_CATCH_UP_MINUTES) {
+                    tracing::warn!("Scheduler skipped from {} to {}, not catching up", last_evaluated, now);
+                    last_evaluated = now - TimeDelta::minutes(1);
+                }
+                while last_evaluated < now {
+                    last_evaluated += TimeDelta::minutes(1);
+                    run_due(&rooms, &entries, &last_evaluated);
+                }
+            }
+        }));
+    }
+
+    /// Stop executing schedules
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Copy the entries for saving
+fn snapshot(entries: &[(ScheduleEntry, CronSchedule)]) -> Vec<ScheduleEntry> {
+    entries.iter().map(|(entry, _)| entry.clone()).collect()
+}
+
+/// Get the current local wall-clock time, truncated to the minute
+fn current_minute() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_second(0).and_then(|now| now.with_nanosecond(0)).unwrap_or(now)
+}
+
+/// Spawn the actions of the enabled entries due at `minute`
+fn run_due(rooms: &BTreeMap<RoomId, Room>, entries: &Mutex<Vec<(ScheduleEntry, CronSchedule)>>, minute: &NaiveDateTime) {
+    let due: Vec<ScheduleEntry> = entries
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(entry, cron)| entry.enabled && cron.matches(minute))
+        .map(|(entry, _)| entry.clone())
+        .collect();
+
+    for entry in due {
+        let Some(room) = rooms.get(&entry.room_id).cloned() else {
+            tracing::warn!("Schedule {} refers to unknown room {}", entry.id, entry.room_id);
+            continue;
+        };
+
+        tracing::info!("Running schedule {} on room {}", entry.id, room.name());
+        tokio::spawn(async move {
+            if let Err(e) = entry.action.execute(&room).await {
+                tracing::warn!("Schedule {} failed: {}", entry.id, e);
+            }
+        });
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_field_expands_lists_ranges_and_steps() {
+        assert_eq!(parse_field("*", 0, 5).unwrap(), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(parse_field("3", 0, 59).unwrap(), vec![3]);
+        assert_eq!(parse_field("5,1,5", 0, 59).unwrap(), vec![1, 5]);
+        assert_eq!(parse_field("10-13", 0, 59).unwrap(), vec![10, 11, 12, 13]);
+        assert_eq!(parse_field("*/15", 0, 59).unwrap(), vec![0, 15, 30, 45]);
+        assert_eq!(parse_field("0-30/10,45", 0, 59).unwrap(), vec![0, 10, 20, 30, 45]);
+    }
+
+    #[test]
+    fn parse_field_rejects_invalid_values() {
+        for field in ["", "60", "5-1", "*/0", "a", "1-", "-1", "1/x", "0-60"] {
+            assert!(parse_field(field, 0, 59).is_err(), "{:?} was accepted", field);
+        }
+        assert!(parse_field("0", 1, 31).is_err());
+    }
+
+    #[test]
+    fn parse_normalizes_sunday() {
+        let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(schedule.days_of_week, vec![0]);
+        // 2026-10-18 is a Sunday
+        assert!(schedule.matches(&at(2026, 10, 18, 0, 0)));
+    }
+
+    #[test]
+    fn matches_every_field() {
+        let schedule = CronSchedule::parse("30 22 * 10 *").unwrap();
+        assert!(schedule.matches(&at(2026, 10, 17, 22, 30)));
+        assert!(!schedule.matches(&at(2026, 10, 17, 22, 31)));
+        assert!(!schedule.matches(&at(2026, 10, 17, 21, 30)));
+        assert!(!schedule.matches(&at(2026, 11, 17, 22, 30)));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Monday
+        let schedule = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert!(schedule.matches(&at(2026, 10, 1, 0, 0)));
+        assert!(schedule.matches(&at(2026, 10, 5, 0, 0)));
+        assert!(!schedule.matches(&at(2026, 10, 2, 0, 0)));
+    }
+
+    #[test]
+    fn one_restricted_day_field_must_match() {
+        // Mondays only: the wildcard day-of-month doesn't widen it
+        let schedule = CronSchedule::parse("0 0 * * 1").unwrap();
+        assert!(schedule.matches(&at(2026, 10, 5, 0, 0)));
+        assert!(!schedule.matches(&at(2026, 10, 1, 0, 0)));
+
+        // A stepped wildcard still counts as unrestricted
+        let schedule = CronSchedule::parse("0 0 */2 * 1").unwrap();
+        assert!(!schedule.matches(&at(2026, 10, 1, 0, 0)));
+        assert!(!schedule.matches(&at(2026, 10, 12, 0, 0)));
+        assert!(schedule.matches(&at(2026, 10, 19, 0, 0)));
+    }
+}
@@ -1,30 +1,101 @@
-use crate::connection::Connection;
-use crate::error::Result;
-use crate::protocol::{Method, Request};
-use crate::subscription::StateReceiver;
-use std::sync::Arc;
+use crate::connection::{ConnectOptions, Connection, ConnectionStatus, LagPolicy};
+use crate::error::{AscendError, Result};
+use crate::protocol::{Method, Request, Targets};
+use crate::subscription::{RoomReceiver, StateReceiver};
+use crate::transport::Transport;
+use crate::types::{Capabilities, Device, DeviceId, Entity, RoomId, TargetsResponse};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::watch;
 
 /// Connection to a specific speaker
+///
+/// Owns the WebSocket to one speaker's API. An `AscendClient` wraps one, and
+/// several clients or rooms can share it to reuse a single connection.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::{AscendClient, SpeakerConnection};
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let speaker = Arc::new(SpeakerConnection::connect("192.168.1.100".to_string(), 8768).await?);
+///     let state = speaker.request_network_state().await?;
+///     println!("{}", serde_json::to_string_pretty(&state)?);
+///
+///     let client = AscendClient::from_connection(speaker.clone()).await;
+///     println!("{} rooms, connected: {}", client.rooms().await?.len(), speaker.is_connected());
+///     Ok(())
+/// }
+/// ```
 pub struct SpeakerConnection {
     ip: String,
     port: u16,
     connection: Arc<Connection>,
+    /// Firmware capabilities, once detected
+    capabilities: OnceLock<Capabilities>,
 }
 
 impl SpeakerConnection {
-    /// Connect to a speaker at the given IP and port
+    /// Connect to a speaker at the given IP address or host name and port
     pub async fn connect(ip: String, port: u16) -> Result<Self> {
-        let url = format!("ws://{}:{}", ip, port);
-        let connection = Connection::connect(url).await?;
+        Self::connect_with(ip, port, ConnectOptions::default()).await
+    }
+
+    /// Connect to a speaker at the given IP address or host name and port with the given options
+    pub async fn connect_with(ip: String, port: u16, options: ConnectOptions) -> Result<Self> {
+        let url = if ip.contains(':') {
+            // IPv6 literal
+            format!("ws://[{}]:{}", ip, port)
+        } else {
+            format!("ws://{}:{}", ip, port)
+        };
+        let connection = Connection::connect_with(url, options).await?;
 
         Ok(Self {
             ip,
             port,
             connection: Arc::new(connection),
+            capabilities: OnceLock::new(),
+        })
+    }
+
+    /// Create a speaker connection over a custom transport
+    pub fn with_transport(transport: impl Transport, options: ConnectOptions) -> Self {
+        Self {
+            ip: "transport".to_string(),
+            port: 0,
+            connection: Arc::new(Connection::with_transport(transport, options)),
+            capabilities: OnceLock::new(),
+        }
+    }
+
+    /// Create a speaker connection that is already closed, for detached rooms
+    pub(crate) fn detached() -> Self {
+        Self {
+            ip: "detached".to_string(),
+            port: 0,
+            connection: Arc::new(Connection::detached(ConnectOptions::default())),
+            capabilities: OnceLock::new(),
+        }
+    }
+
+    /// Create a speaker connection answering from a recorded session
+    #[cfg(feature = "recorder")]
+    pub fn replay(path: impl AsRef<std::path::Path>, options: ConnectOptions) -> Result<Self> {
+        let connection = Connection::replay(path, options)?;
+
+        Ok(Self {
+            ip: "replay".to_string(),
+            port: 0,
+            connection: Arc::new(connection),
+            capabilities: OnceLock::new(),
         })
     }
 
-    /// Get the speaker's IP address
+    /// Get the speaker's IP address, or its host name if it was connected to by name
     pub fn ip(&self) -> &str {
         &self.ip
     }
@@ -34,8 +105,69 @@ impl SpeakerConnection {
         self.port
     }
 
+    /// Watch the status of the link to the speaker
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.connection.status()
+    }
+
+    /// Get the current status of the link to the speaker
+    pub fn status(&self) -> ConnectionStatus {
+        *self.connection.status().borrow()
+    }
+
+    /// Check if the WebSocket to the speaker is open
+    pub fn is_connected(&self) -> bool {
+        self.status() == ConnectionStatus::Connected
+    }
+
+    /// Get the options the connection was opened with
+    pub fn options(&self) -> &ConnectOptions {
+        self.connection.options()
+    }
+
+    /// Get the room document fields seen that this crate doesn't know
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.connection.unknown_fields()
+    }
+
+    /// Tear down and re-establish the WebSocket, replaying subscriptions
+    pub async fn reconnect(&self) -> Result<()> {
+        self.connection.reconnect().await
+    }
+
+    /// Query the firmware version and record the supported features
+    ///
+    /// Firmware without the `version` endpoint, or a transport failure, leaves
+    /// the capabilities undetermined, in which case no feature checks are applied.
+    pub async fn detect_capabilities(&self) -> Option<Capabilities> {
+        let request = Request::new("version", Method::Read);
+        let capabilities = match self.connection.send_request(request).await {
+            Ok(response) => Capabilities::from_version(response.data.as_ref()?),
+            Err(e) => {
+                tracing::debug!("Failed to detect capabilities: {}", e);
+                return None;
+            }
+        };
+        Some(self.capabilities.get_or_init(|| capabilities).clone())
+    }
+
+    /// Get the detected firmware capabilities, if detection has run
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.capabilities.get().cloned()
+    }
+
+    /// Fail with `AscendError::Unsupported` if the firmware is known to lack a feature
+    pub(crate) fn require(&self, supported: impl FnOnce(&Capabilities) -> bool, feature: &str) -> Result<()> {
+        match self.capabilities.get() {
+            Some(capabilities) if !supported(capabilities) => {
+                Err(AscendError::Unsupported(feature.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Get the underlying connection
-    pub fn connection(&self) -> Arc<Connection> {
+    pub(crate) fn connection(&self) -> Arc<Connection> {
         self.connection.clone()
     }
 
@@ -46,15 +178,93 @@ impl SpeakerConnection {
 
         response
             .data
-            .ok_or_else(|| crate::error::AscendError::InvalidResponse("No data in network response".to_string()))
+            .ok_or_else(|| AscendError::InvalidResponse("No data in network response".to_string()))
+    }
+
+    /// Request the controllable rooms and devices from the targets endpoint
+    pub async fn request_targets(&self) -> Result<TargetsResponse> {
+        let request = Request::typed::<Targets>(&())?;
+        self.connection.send_typed::<Targets>(request).await
+    }
+
+    /// Request network state and list every entry it contains
+    pub async fn request_entities(&self) -> Result<Vec<Entity>> {
+        let data = self.request_network_state().await?;
+
+        let state_obj = data
+            .get("state")
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| AscendError::InvalidResponse("No state in network response".to_string()))?;
+
+        Ok(state_obj
+            .iter()
+            .filter_map(|(state_id, state_entry)| {
+                let entry_data = state_entry.get("data")?;
+                Some(Entity {
+                    id: state_id.clone(),
+                    entity_type: entry_data.get("type").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    data: entry_data.clone(),
+                })
+            })
+            .collect())
+    }
+
+    /// Request network state and parse the device entries it contains
+    pub async fn request_devices(&self) -> Result<BTreeMap<DeviceId, Device>> {
+        let data = self.request_network_state().await?;
+
+        let state_obj = data
+            .get("state")
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| AscendError::InvalidResponse("No state in network response".to_string()))?;
+
+        let mut devices = BTreeMap::new();
+        for (state_id, state_entry) in state_obj {
+            if let Some(entry_data) = state_entry.get("data") {
+                if entry_data.get("type").and_then(|v| v.as_str()) == Some("device") {
+                    let device_id = entry_data
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(state_id)
+                        .to_string();
+
+                    match serde_json::from_value::<Device>(entry_data.clone()) {
+                        Ok(device) => {
+                            devices.insert(device_id, device);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to parse device {}: {}", device_id, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(devices)
     }
 
     /// Subscribe to state updates from the speaker
+    ///
+    /// Waits for the speaker to accept the subscription.
     pub async fn subscribe_state(&self) -> Result<StateReceiver> {
+        // Subscribe to the broadcast first so no early updates are missed
+        let rx = self.connection.subscribe();
         let request = Request::new("network", Method::Subscribe);
-        self.connection.send_only(request).await?;
+        self.connection.subscribe_request(request).await?;
+
+        Ok(match self.connection.options().lag_policy {
+            LagPolicy::Error => StateReceiver::new(rx),
+            LagPolicy::Resync => StateReceiver::with_resync(rx, Arc::downgrade(&self.connection)),
+        })
+    }
 
+    /// Subscribe to state updates for a single room
+    pub async fn subscribe_room(&self, room_id: RoomId) -> Result<RoomReceiver> {
+        // Subscribe to the broadcast first so no early updates are missed
         let rx = self.connection.subscribe();
-        Ok(StateReceiver::new(rx))
+        let request = Request::new("network", Method::Subscribe);
+        self.connection.subscribe_request(request).await?;
+
+        Ok(RoomReceiver::new(rx, room_id))
     }
 }
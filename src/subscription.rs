@@ -1,46 +1,207 @@
+use crate::connection::Connection;
 use crate::error::{AscendError, Result};
-use crate::types::{Device, DeviceId};
+use crate::protocol::{Method, Request};
+use crate::room::RoomStateDelta;
+use crate::types::{
+    Device, DeviceId, GainData, MuteData,
+    ProtectionEvent, RoomId,
+};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use tokio::sync::broadcast;
 
 /// State update from a subscription
+///
+/// Every subscriber receives its own clone of each update, so large payloads
+/// are shared behind an `Arc`.
 #[derive(Debug, Clone)]
 pub enum StateUpdate {
     /// Room state was updated (raw JSON)
-    RoomUpdate(Box<serde_json::Value>),
+    RoomUpdate(Arc<serde_json::Value>),
 
     /// Device state was updated
-    DeviceUpdate(DeviceId, Device),
+    DeviceUpdate(DeviceId, Arc<Device>),
+
+    /// Parsed room state changed; lists the fields that differ from the previous notify
+    RoomChanged(RoomStateDelta),
+
+    /// Gain of a room changed
+    GainChanged { room: RoomId, gain: GainData, source: ChangeSource },
+
+    /// Mute state of a room changed
+    MuteChanged { room: RoomId, mute: MuteData, source: ChangeSource },
+
+    /// Selected input of a room changed
+    InputChanged { room: RoomId, input: Option<String>, source: ChangeSource },
+
+    /// Selected voicing profile of a room changed
+    VoicingChanged { room: RoomId, voicing: Option<String>, source: ChangeSource },
+
+    /// A preset was applied to a room; carries its ID and, if the room lists it, its name
+    PresetApplied {
+        room: RoomId,
+        preset: Option<String>,
+        name: Option<String>,
+        source: ChangeSource,
+    },
+
+    /// A room entered or left standby, including on its own after an idle timeout
+    StandbyChanged { room: RoomId, sleeping: bool, cause: StandbyCause },
+
+    /// Updates were missed; carries the full network state refetched to recover
+    ///
+    /// Delivered to a lagging subscriber when connected with `LagPolicy::Resync`,
+    /// and to every subscriber when the speaker's numbered notifications skip a
+    /// number.
+    Resync(Arc<serde_json::Value>),
+
+    /// A device reported a limiter, thermal, or other protection event
+    Protection(ProtectionEvent),
+
+    /// A network state entry of a type this crate doesn't model, with its type and raw data
+    UnknownEntity(String, Arc<serde_json::Value>),
+}
+
+impl StateUpdate {
+    /// Get the room this update concerns, if it concerns a single room
+    pub fn room_id(&self) -> Option<RoomId> {
+        match self {
+            Self::RoomUpdate(json) => json
+                .get("id")
+                .and_then(|v| v.as_str())
+                .and_then(|id| id.parse().ok()),
+            Self::RoomChanged(delta) => Some(delta.room_id),
+            Self::GainChanged { room, .. }
+            | Self::MuteChanged { room, .. }
+            | Self::InputChanged { room, .. }
+            | Self::VoicingChanged { room, .. }
+            | Self::PresetApplied { room, .. }
+            | Self::StandbyChanged { room, .. } => Some(*room),
+            Self::DeviceUpdate(..)
+            | Self::Resync(_)
+            | Self::Protection(_)
+            | Self::UnknownEntity(..) => None,
+        }
+    }
+}
+
+/// Who caused a state change
+///
+/// The speaker doesn't report which controller made a change, so it is
+/// inferred: a room change within a few seconds of this connection sending a
+/// command to that room is attributed to this connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSource {
+    /// Most likely caused by a command sent over this connection
+    Local,
+
+    /// Caused by another controller, such as the phone app or a volume knob
+    External,
+}
+
+/// Why a room entered or left standby
+///
+/// Like `ChangeSource`, the cause is inferred: a change right after this
+/// connection sent a command to the room is `Local`. The speaker doesn't
+/// report why it changed otherwise, so another controller and the speaker's
+/// own idle timeout are both `External`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StandbyCause {
+    /// Most likely caused by a command sent over this connection
+    Local,
+
+    /// Caused by another controller, such as the phone app, or by the
+    /// speaker itself after an idle timeout
+    External,
+}
+
+impl StandbyCause {
+    /// Infer the cause of a standby change between two snapshots of a room
+    pub(crate) fn infer(source: ChangeSource) -> Self {
+        match source {
+            ChangeSource::Local => Self::Local,
+            ChangeSource::External => Self::External,
+        }
+    }
+
+    /// Get the controller the change is attributed to
+    pub fn source(&self) -> ChangeSource {
+        match self {
+            Self::Local => ChangeSource::Local,
+            Self::External => ChangeSource::External,
+        }
+    }
+}
+
+/// A room entering or leaving standby, as received from `Room::on_standby_change`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StandbyChange {
+    /// Whether the room is now in standby
+    pub sleeping: bool,
+    pub cause: StandbyCause,
 }
 
 /// Receiver for state updates
 pub struct StateReceiver {
     rx: broadcast::Receiver<StateUpdate>,
+    /// Connection to refetch state from on lag, when `LagPolicy::Resync` is in effect
+    resync: Option<Weak<Connection>>,
+    /// Runtime the receiver was created on, if any, used by `blocking_iter`
+    runtime: Option<tokio::runtime::Handle>,
 }
 
 impl StateReceiver {
     /// Create a new state receiver
     pub(crate) fn new(rx: broadcast::Receiver<StateUpdate>) -> Self {
-        Self { rx }
+        Self {
+            rx,
+            resync: None,
+            runtime: tokio::runtime::Handle::try_current().ok(),
+        }
+    }
+
+    /// Create a state receiver that recovers from lag by refetching state
+    pub(crate) fn with_resync(rx: broadcast::Receiver<StateUpdate>, connection: Weak<Connection>) -> Self {
+        Self {
+            rx,
+            resync: Some(connection),
+            runtime: tokio::runtime::Handle::try_current().ok(),
+        }
     }
 
     /// Receive the next state update
     ///
     /// Returns `None` if all senders have been dropped (connection closed).
+    /// If the receiver fell behind, returns a `ChannelError`, or with
+    /// `LagPolicy::Resync` a `StateUpdate::Resync` carrying the current state.
     pub async fn recv(&mut self) -> Result<StateUpdate> {
-        self.rx
-            .recv()
-            .await
-            .map_err(|e| match e {
-                broadcast::error::RecvError::Closed => AscendError::ConnectionClosed,
-                broadcast::error::RecvError::Lagged(n) => {
-                    AscendError::ChannelError(format!("Lagged by {} messages", n))
-                }
-            })
+        match self.rx.recv().await {
+            Ok(update) => Ok(update),
+            Err(broadcast::error::RecvError::Closed) => Err(AscendError::ConnectionClosed),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                let Some(connection) = self.resync.as_ref().and_then(Weak::upgrade) else {
+                    return Err(AscendError::ChannelError(format!("Lagged by {} messages", n)));
+                };
+
+                tracing::debug!("State receiver lagged by {} messages, resyncing", n);
+                let response = connection.send_request(Request::new("network", Method::Read)).await?;
+                let data = response
+                    .data
+                    .ok_or_else(|| AscendError::InvalidResponse("No data in network response".to_string()))?;
+                Ok(StateUpdate::Resync(Arc::new(data)))
+            }
+        }
     }
 
     /// Try to receive a state update without blocking
     ///
     /// Returns `None` if no message is available.
+    /// Lag is always reported as a `ChannelError` here, regardless of the lag policy.
     pub fn try_recv(&mut self) -> Result<Option<StateUpdate>> {
         match self.rx.try_recv() {
             Ok(update) => Ok(Some(update)),
@@ -51,4 +212,323 @@ impl StateReceiver {
             }
         }
     }
+
+    /// Receive the next state update, waiting at most `timeout`
+    ///
+    /// Returns `None` if no update arrived in time.
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<StateUpdate>> {
+        match tokio::time::timeout(timeout, self.recv()).await {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Drain all pending updates and return only the newest one
+    ///
+    /// Returns `None` if no update is pending. Falling behind is not an error
+    /// here, since the stale updates would be discarded anyway.
+    pub fn latest(&mut self) -> Result<Option<StateUpdate>> {
+        let mut latest = None;
+        loop {
+            match self.rx.try_recv() {
+                Ok(update) => latest = Some(update),
+                Err(broadcast::error::TryRecvError::Empty) => return Ok(latest),
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(broadcast::error::TryRecvError::Closed) => {
+                    return match latest {
+                        Some(update) => Ok(Some(update)),
+                        None => Err(AscendError::ConnectionClosed),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Merge bursts of updates for the same room (see `CoalescingReceiver`)
+    pub fn coalesce(self, window: Duration) -> CoalescingReceiver {
+        CoalescingReceiver::new(self, window)
+    }
+
+    /// Convert into a blocking iterator for use outside async code
+    ///
+    /// Each call to `next` blocks the current thread on the runtime the
+    /// receiver was created on. The iterator ends when the connection closes.
+    /// If the receiver was created outside a Tokio runtime, the iterator yields
+    /// a single `InvalidArgument` error and ends. Must not be used from within
+    /// an async context, where blocking panics.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::AscendClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let updates = client.subscribe_state().await?;
+    ///
+    ///     // E.g. a GUI thread
+    ///     std::thread::spawn(move || {
+    ///         for update in updates.blocking_iter() {
+    ///             println!("State update: {:?}", update);
+    ///         }
+    ///     });
+    ///
+    ///     tokio::signal::ctrl_c().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn blocking_iter(mut self) -> impl Iterator<Item = Result<StateUpdate>> + Send {
+        let mut runtime = Some(self.runtime.clone());
+        std::iter::from_fn(move || {
+            let Some(handle) = runtime.as_ref()? else {
+                runtime = None;
+                return Some(Err(AscendError::InvalidArgument(
+                    "State receiver was not created on a Tokio runtime".to_string(),
+                )));
+            };
+            match handle.block_on(self.recv()) {
+                Err(AscendError::ConnectionClosed) => None,
+                result => Some(result),
+            }
+        })
+    }
+
+    /// Convert into a `Stream` of state updates
+    ///
+    /// The stream ends when the connection closes. If the receiver falls
+    /// behind, a `ChannelError` item is yielded and the stream continues with
+    /// the oldest update still available.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dutchdutch_ascend::{AscendClient, StateUpdate};
+    /// use futures_util::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+    ///     let mut gains = client
+    ///         .subscribe_state()
+    ///         .await?
+    ///         .into_stream()
+    ///         .filter_map(|update| async move {
+    ///             match update {
+    ///                 Ok(StateUpdate::GainChanged { room, gain, .. }) => Some((room, gain.global)),
+    ///                 _ => None,
+    ///             }
+    ///         })
+    ///         .boxed();
+    ///
+    ///     while let Some((room, gain)) = gains.next().await {
+    ///         println!("Room {} gain: {} dB", room, gain);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_stream(self) -> impl Stream<Item = Result<StateUpdate>> + Send + Unpin {
+        Box::pin(futures_util::stream::unfold(self, |mut receiver| async move {
+            match receiver.recv().await {
+                Err(AscendError::ConnectionClosed) => None,
+                result => Some((result, receiver)),
+            }
+        }))
+    }
+}
+
+/// State receiver that merges bursts of updates for the same room
+///
+/// Speakers emit a flurry of notifies while a preset is applied. When a room
+/// update arrives, further updates are collected for `window`; within that
+/// batch, repeated updates of the same kind for the same room are merged: the
+/// newest value wins, `RoomChanged` deltas are combined, and partial
+/// `RoomUpdate` documents are overlaid key by key. Updates that do not concern
+/// a room, such as `Resync`, are passed through in order and split the batch,
+/// so room updates are never merged across them.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::AscendClient;
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///     let mut updates = client
+///         .subscribe_state()
+///         .await?
+///         .coalesce(Duration::from_millis(200));
+///
+///     while let Ok(update) = updates.recv().await {
+///         println!("State update: {:?}", update);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct CoalescingReceiver {
+    inner: StateReceiver,
+    window: Duration,
+    /// Merged updates waiting to be delivered
+    pending: VecDeque<StateUpdate>,
+    /// Error hit while collecting a batch, delivered after the batch
+    error: Option<AscendError>,
+}
+
+impl CoalescingReceiver {
+    fn new(inner: StateReceiver, window: Duration) -> Self {
+        Self {
+            inner,
+            window,
+            pending: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Receive the next (possibly merged) state update
+    pub async fn recv(&mut self) -> Result<StateUpdate> {
+        if let Some(update) = self.pending.pop_front() {
+            return Ok(update);
+        }
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let first = self.inner.recv().await?;
+        if first.room_id().is_none() {
+            return Ok(first);
+        }
+
+        // Collect everything that arrives within the window
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + self.window;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match self.inner.recv_timeout(remaining).await {
+                Ok(Some(update)) => batch.push(update),
+                Ok(None) => break,
+                Err(e) => {
+                    self.error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        self.pending = Self::merge(batch);
+        Ok(self
+            .pending
+            .pop_front()
+            .expect("merged batch holds at least the first update"))
+    }
+
+    /// Merge updates of the same kind for the same room, keeping the position
+    /// of the first occurrence, but never across an update not tied to a room
+    fn merge(batch: Vec<StateUpdate>) -> VecDeque<StateUpdate> {
+        let mut merged: VecDeque<StateUpdate> = VecDeque::with_capacity(batch.len());
+        let mut positions = HashMap::new();
+
+        for update in batch {
+            let Some(room_id) = update.room_id() else {
+                positions.clear();
+                merged.push_back(update);
+                continue;
+            };
+
+            let key = (room_id, std::mem::discriminant(&update));
+            match positions.get(&key) {
+                Some(&index) => match (&mut merged[index], update) {
+                    (StateUpdate::RoomChanged(existing), StateUpdate::RoomChanged(delta)) => {
+                        for field in delta.changed {
+                            if !existing.changed.contains(&field) {
+                                existing.changed.push(field);
+                            }
+                        }
+                    }
+                    (StateUpdate::RoomUpdate(existing), StateUpdate::RoomUpdate(document)) => {
+                        // A complete document replaces the state; a partial one only changes its keys
+                        let complete = ["name", "gain", "mute"].iter().all(|key| document.get(*key).is_some());
+                        match (Arc::make_mut(existing), document.as_object()) {
+                            (serde_json::Value::Object(existing), Some(document)) if !complete => {
+                                for (key, value) in document {
+                                    existing.insert(key.clone(), value.clone());
+                                }
+                            }
+                            _ => *existing = document,
+                        }
+                    }
+                    (existing, update) => *existing = update,
+                },
+                None => {
+                    positions.insert(key, merged.len());
+                    merged.push_back(update);
+                }
+            }
+        }
+
+        merged
+    }
+}
+
+/// Receiver for state updates of a single room
+pub struct RoomReceiver {
+    rx: broadcast::Receiver<StateUpdate>,
+    room_id: RoomId,
+}
+
+impl RoomReceiver {
+    /// Create a new receiver for the given room
+    pub(crate) fn new(rx: broadcast::Receiver<StateUpdate>, room_id: RoomId) -> Self {
+        Self { rx, room_id }
+    }
+
+    /// Receive the next state update for this room
+    ///
+    /// Updates for other rooms and device-level updates are skipped.
+    pub async fn recv(&mut self) -> Result<StateUpdate> {
+        loop {
+            match self.rx.recv().await {
+                Ok(update) if update.room_id() == Some(self.room_id) => return Ok(update),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Err(AscendError::ConnectionClosed),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    return Err(AscendError::ChannelError(format!("Lagged by {} messages", n)));
+                }
+            }
+        }
+    }
+}
+
+/// Receiver for standby changes of a single room
+pub struct StandbyReceiver {
+    rx: broadcast::Receiver<StateUpdate>,
+    room_id: RoomId,
+}
+
+impl StandbyReceiver {
+    /// Create a new standby receiver for the given room
+    pub(crate) fn new(rx: broadcast::Receiver<StateUpdate>, room_id: RoomId) -> Self {
+        Self { rx, room_id }
+    }
+
+    /// Receive the next standby change of this room
+    ///
+    /// Updates for other rooms and other kinds of updates are skipped.
+    pub async fn recv(&mut self) -> Result<StandbyChange> {
+        loop {
+            match self.rx.recv().await {
+                Ok(StateUpdate::StandbyChanged { room, sleeping, cause }) if room == self.room_id => {
+                    return Ok(StandbyChange { sleeping, cause });
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Err(AscendError::ConnectionClosed),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    return Err(AscendError::ChannelError(format!("Lagged by {} messages", n)));
+                }
+            }
+        }
+    }
 }
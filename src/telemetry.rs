@@ -0,0 +1,78 @@
+//! OpenTelemetry trace export over OTLP (requires the `otel` feature)
+//!
+//! Requests, room setters, and discovery run inside `tracing` spans carrying
+//! the endpoint, method, target, and request ID. [`init_otlp`] installs a global
+//! subscriber that exports those spans, so request latency and failures show up
+//! next to the rest of an automation system's traces.
+//!
+//! Applications that already install their own subscriber should add a
+//! `tracing_opentelemetry` layer to it instead.
+
+use crate::error::{AscendError, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Flushes and shuts down trace export when dropped
+///
+/// Keep it alive for as long as spans should be exported, typically for the
+/// whole of `main`.
+#[must_use = "trace export stops when the guard is dropped"]
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("Failed to shut down trace export: {}", e);
+        }
+    }
+}
+
+/// Install a global `tracing` subscriber exporting spans over OTLP/HTTP
+///
+/// The collector endpoint is taken from the standard
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` / `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`
+/// environment variables (default `http://localhost:4318`). Events are also
+/// logged to stdout.
+///
+/// Fails if a global subscriber is already installed.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::AscendClient;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let _telemetry = dutchdutch_ascend::telemetry::init_otlp("studio-automation")?;
+///
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///     client.room().await?.set_gain(-20.0).await?;
+///     Ok(())
+/// }
+/// ```
+pub fn init_otlp(service_name: impl Into<String>) -> Result<OtelGuard> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| AscendError::Telemetry(format!("Invalid OTLP exporter configuration: {}", e)))?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name.into()).build())
+        .build();
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .with(tracing_subscriber::fmt::layer())
+        .try_init()
+        .map_err(|e| AscendError::Telemetry(format!("Could not install tracing subscriber: {}", e)))?;
+
+    Ok(OtelGuard { provider })
+}
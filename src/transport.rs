@@ -0,0 +1,230 @@
+use crate::error::{AscendError, Result};
+use futures_util::stream::FuturesUnordered;
+use futures_util::{SinkExt, StreamExt};
+use socket2::{SockRef, TcpKeepalive};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// Unanswered TCP keepalive probes before the OS drops the connection
+const TCP_KEEPALIVE_RETRIES: u32 = 2;
+
+/// A bidirectional message channel a connection runs over
+///
+/// The default is [`WebSocketTransport`]. Other implementations can stand in
+/// for a speaker, e.g. an in-memory duplex channel in tests, to exercise
+/// request/response correlation, timeouts, and notification parsing without
+/// a network.
+///
+/// Each message is one JSON protocol document.
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::{AscendClient, ConnectOptions, Result, Transport};
+/// use serde_json::{json, Value};
+/// use std::collections::VecDeque;
+///
+/// /// Answers every request with a network state holding one room
+/// #[derive(Default)]
+/// struct FakeSpeaker {
+///     replies: VecDeque<String>,
+/// }
+///
+/// impl Transport for FakeSpeaker {
+///     async fn send(&mut self, text: String) -> Result<()> {
+///         let request: Value = serde_json::from_str(&text)?;
+///         let meta = &request["meta"];
+///         let room = json!({
+///             "type": "room",
+///             "id": "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d",
+///             "name": "Studio",
+///             "gain": { "global": -20.0 },
+///             "mute": { "global": false },
+///         });
+///         let reply = json!({
+///             "meta": { "id": meta["id"], "endpoint": meta["endpoint"], "method": meta["method"] },
+///             "data": { "state": { "room": { "data": room } } },
+///         });
+///         self.replies.push_back(reply.to_string());
+///         Ok(())
+///     }
+///
+///     async fn recv(&mut self) -> Option<Result<String>> {
+///         match self.replies.pop_front() {
+///             Some(reply) => Some(Ok(reply)),
+///             None => std::future::pending().await,
+///         }
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let client = AscendClient::with_transport(FakeSpeaker::default(), ConnectOptions::default()).await;
+///     assert_eq!(client.room().await?.name(), "Studio");
+///     Ok(())
+/// }
+/// ```
+pub trait Transport: Send + 'static {
+    /// Send one message
+    fn send(&mut self, text: String) -> impl Future<Output = Result<()>> + Send;
+
+    /// Receive the next message, or `None` once the transport is closed
+    ///
+    /// Must be cancel-safe: the future may be dropped before completing when a
+    /// message is sent in the meantime, and no message may be lost when it is.
+    fn recv(&mut self) -> impl Future<Output = Option<Result<String>>> + Send;
+
+    /// Send a keepalive probe (see `ConnectOptions::keepalive`)
+    ///
+    /// Transports without one do nothing.
+    fn ping(&mut self) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    /// When anything, including a keepalive answer, was last received
+    ///
+    /// Used to drop half-open connections. Transports that can't tell return
+    /// `None` and are never considered half-open.
+    fn last_received(&self) -> Option<Instant> {
+        None
+    }
+}
+
+/// Transport over a WebSocket connection to the speaker
+pub struct WebSocketTransport {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    /// When the last frame of any kind arrived
+    last_received: Instant,
+}
+
+impl WebSocketTransport {
+    /// Wrap an established WebSocket stream
+    pub fn new(stream: WebSocketStream<MaybeTlsStream<TcpStream>>) -> Self {
+        Self {
+            stream,
+            last_received: Instant::now(),
+        }
+    }
+
+    /// Enable TCP keepalive on the underlying socket
+    ///
+    /// The OS starts probing after the connection was idle for `interval`,
+    /// repeats every `interval`, and gives up after two unanswered probes. On
+    /// Linux, data left unacknowledged for as long also drops the connection,
+    /// covering requests that are waiting for a reply. Only plain (`ws://`)
+    /// connections are supported; for others this does nothing.
+    pub fn set_tcp_keepalive(&self, interval: Duration) -> std::io::Result<()> {
+        let MaybeTlsStream::Plain(tcp) = self.stream.get_ref() else {
+            return Ok(());
+        };
+        let socket = SockRef::from(tcp);
+        let keepalive = TcpKeepalive::new().with_time(interval);
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "windows",
+        ))]
+        let keepalive = keepalive.with_interval(interval).with_retries(TCP_KEEPALIVE_RETRIES);
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        socket.set_tcp_user_timeout(Some(interval * (TCP_KEEPALIVE_RETRIES + 1)))?;
+        socket.set_tcp_keepalive(&keepalive)
+    }
+}
+
+impl Transport for WebSocketTransport {
+    async fn send(&mut self, text: String) -> Result<()> {
+        self.stream.send(Message::Text(text)).await?;
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Option<Result<String>> {
+        loop {
+            let message = self.stream.next().await?;
+            if message.is_ok() {
+                self.last_received = Instant::now();
+            }
+            match message {
+                Ok(Message::Text(text)) => return Some(Ok(text)),
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(AscendError::WebSocket(Box::new(e)))),
+            }
+        }
+    }
+
+    async fn ping(&mut self) -> Result<()> {
+        self.stream.send(Message::Ping(Vec::new())).await?;
+        Ok(())
+    }
+
+    fn last_received(&self) -> Option<Instant> {
+        Some(self.last_received)
+    }
+}
+
+/// Delay before racing the next address while earlier attempts are pending (RFC 8305)
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Open a TCP connection to a host name or IP address
+///
+/// All A and AAAA records are tried, alternating between address families.
+/// Each attempt gets a head start of 250 ms before the next one is started
+/// alongside it, and the first to connect wins, so an unreachable address
+/// (such as a stale IPv6 record) doesn't hold up the others.
+pub(crate) async fn connect_tcp(host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut remaining = interleave_families(tokio::net::lookup_host((host, port)).await?.collect()).into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+
+    loop {
+        if let Some(addr) = remaining.next() {
+            attempts.push(TcpStream::connect(addr));
+        } else if attempts.is_empty() {
+            return Err(last_error.unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("Could not resolve host: {}", host))
+            }));
+        }
+
+        let result = if remaining.len() > 0 {
+            tokio::select! {
+                result = attempts.next() => result,
+                _ = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY) => continue,
+            }
+        } else {
+            attempts.next().await
+        };
+        match result {
+            Some(Ok(stream)) => return Ok(stream),
+            Some(Err(e)) => {
+                tracing::debug!("Connection attempt to {} failed: {}", host, e);
+                last_error = Some(e);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Order addresses so IPv6 and IPv4 alternate, starting with the family listed first
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_is_v6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|addr| addr.is_ipv6() == first_is_v6);
+
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+    interleaved
+}
@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
 use uuid::Uuid;
 
 /// Room identifier
@@ -12,7 +13,144 @@ pub type DeviceId = String;
 pub type PositionId = String;
 
 /// Gain value in decibels
-pub type GainValue = f64;
+#[deprecated(note = "use `GainDb`")]
+pub type GainValue = GainDb;
+
+/// Gain in decibels
+///
+/// Serialized as a plain number and formatted like an `f64`, without a unit.
+/// Setters accept anything convertible into a `GainDb`, including `f64`
+/// literals such as `-20.0`.
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::{GainDb, GainLimits};
+///
+/// let limits = GainLimits { min: -80.0, max: 0.0, step: 0.5 };
+/// let gain = GainDb::new(-1.2) + GainDb::new(3.0);
+/// assert_eq!(gain.clamp_to(&limits), GainDb::new(0.0));
+/// assert_eq!(GainDb::new(-20.3).snap_to(&limits), GainDb::new(-20.5));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GainDb(f64);
+
+impl GainDb {
+    /// Create a gain from a value in dB
+    pub const fn new(db: f64) -> Self {
+        Self(db)
+    }
+
+    /// Get the value in dB
+    pub const fn db(self) -> f64 {
+        self.0
+    }
+
+    /// Get the absolute value
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Get the lower of two gains
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    /// Get the higher of two gains
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+
+    /// Check that the gain is neither infinite nor NaN
+    pub fn is_finite(self) -> bool {
+        self.0.is_finite()
+    }
+
+    /// Limit the gain to the range of `limits`
+    ///
+    /// Unlike `f64::clamp`, doesn't panic if the speaker reports a minimum
+    /// above its maximum.
+    pub fn clamp_to(self, limits: &GainLimits) -> Self {
+        Self(self.0.min(limits.max).max(limits.min))
+    }
+
+    /// Round the gain to the nearest step of `limits`, counted from its minimum
+    ///
+    /// The result is clamped to the range. A step of zero only clamps.
+    pub fn snap_to(self, limits: &GainLimits) -> Self {
+        if limits.step <= 0.0 {
+            return self.clamp_to(limits);
+        }
+        let steps = ((self.0 - limits.min) / limits.step).round();
+        Self(limits.min + steps * limits.step).clamp_to(limits)
+    }
+}
+
+impl From<f64> for GainDb {
+    fn from(db: f64) -> Self {
+        Self(db)
+    }
+}
+
+impl From<GainDb> for f64 {
+    fn from(gain: GainDb) -> Self {
+        gain.0
+    }
+}
+
+impl std::ops::Add for GainDb {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for GainDb {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::AddAssign for GainDb {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl std::ops::SubAssign for GainDb {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl std::ops::Neg for GainDb {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl std::fmt::Display for GainDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::str::FromStr for GainDb {
+    type Err = std::num::ParseFloatError;
+
+    /// Parse a number of dB, with or without a `dB` suffix
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_suffix("dB").or_else(|| s.strip_suffix("db")).unwrap_or(s);
+        s.trim_end().parse().map(Self)
+    }
+}
 
 /// Mute state
 pub type MuteState = bool;
@@ -32,13 +170,122 @@ pub struct Device {
     /// Licensed features
     #[serde(default)]
     pub licenses: Vec<String>,
+
+    /// Serial number
+    #[serde(default)]
+    pub serial: Option<String>,
+
+    /// Installed firmware version
+    #[serde(default)]
+    #[serde(rename = "firmwareVersion")]
+    pub firmware_version: Option<String>,
+}
+
+impl Device {
+    /// Determine the product model from the device's tags
+    pub fn model(&self) -> ProductModel {
+        ProductModel::from_tags(&self.tags)
+    }
+}
+
+/// Speaker product model
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProductModel {
+    /// Dutch & Dutch 8c active speaker
+    EightC,
+    /// Subwoofer
+    Subwoofer,
+    /// Model not recognized by this library (first product tag, if any)
+    Unknown(String),
+}
+
+impl ProductModel {
+    /// Determine the product model from a list of product tags
+    pub fn from_tags(tags: &[String]) -> Self {
+        if tags.iter().any(|tag| tag.eq_ignore_ascii_case("8c")) {
+            Self::EightC
+        } else if tags.iter().any(|tag| tag.eq_ignore_ascii_case("subwoofer")) {
+            Self::Subwoofer
+        } else {
+            Self::Unknown(tags.first().cloned().unwrap_or_default())
+        }
+    }
+}
+
+/// Information about a speaker system, for "About" screens and feature gating
+#[derive(Debug, Clone)]
+pub struct SystemInfo {
+    /// Address of the master speaker serving the API
+    pub master_ip: String,
+
+    /// Port of the master speaker's API
+    pub port: u16,
+
+    /// API/protocol version reported by the master, if available
+    pub api_version: Option<String>,
+
+    /// All devices with model, serial number, and firmware version
+    pub devices: BTreeMap<DeviceId, Device>,
+}
+
+/// Features supported by the speaker's firmware
+///
+/// Determined from the `version` endpoint after connecting: firmware listing
+/// `features` reports exactly those, and firmware without a feature list is
+/// assumed to support everything. Firmware without the endpoint leaves the
+/// capabilities unknown, so no feature is rejected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// API/protocol version reported by the master, if available
+    pub api_version: Option<String>,
+
+    /// Presets can be created and deleted
+    pub supports_preset_create: bool,
+
+    /// The linear phase filter can be toggled
+    pub supports_linear_phase: bool,
+
+    /// Gain can be set per position, not only globally
+    pub supports_per_position_gain: bool,
+}
+
+impl Capabilities {
+    /// Derive capabilities from the `version` response data
+    pub(crate) fn from_version(data: &serde_json::Value) -> Self {
+        let api_version = data.get("api").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let features: Option<Vec<&str>> = data
+            .get("features")
+            .and_then(|v| v.as_array())
+            .map(|features| features.iter().filter_map(|f| f.as_str()).collect());
+        let supports = |feature: &str| features.as_ref().is_none_or(|f| f.contains(&feature));
+
+        Self {
+            api_version,
+            supports_preset_create: supports("presetCreate"),
+            supports_linear_phase: supports("linearPhase"),
+            supports_per_position_gain: supports("positionGain"),
+        }
+    }
+}
+
+/// A member device of a room and the position it occupies
+#[derive(Debug, Clone)]
+pub struct RoomMember {
+    /// Device identifier
+    pub device_id: DeviceId,
+
+    /// Position of the device in the room
+    pub position: PositionId,
+
+    /// Device details from the network state, if the device entry was found
+    pub device: Option<Device>,
 }
 
 /// Gain data structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GainData {
     /// Global gain value in dB
-    pub global: f64,
+    pub global: GainDb,
 
     /// Gain limits
     #[serde(default)]
@@ -63,7 +310,7 @@ impl GainData {
 }
 
 /// Gain limits
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct GainLimits {
     #[serde(default = "default_min")]
     pub min: f64,
@@ -82,7 +329,7 @@ fn default_step() -> f64 {
 }
 
 /// Mute data structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MuteData {
     /// Global mute state
     pub global: bool,
@@ -110,7 +357,7 @@ impl MuteData {
 }
 
 /// Voicing profile
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VoicingProfile {
     pub name: String,
     pub sub: f64,
@@ -118,7 +365,87 @@ pub struct VoicingProfile {
     pub treble: f64,
     #[serde(default)]
     #[serde(rename = "paramEQ")]
-    pub param_eq: BTreeMap<String, serde_json::Value>,
+    pub param_eq: ParamEq,
+
+    /// Fields not known to this crate, kept so the profile serializes unchanged
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Parametric EQ settings of a voicing profile, parsed on first access
+///
+/// Voicing profiles carry large EQ tables that most clients never read, so
+/// room updates only keep a reference to the `paramEQ` JSON in the room
+/// document. Clones share the document and the parsed bands.
+#[derive(Clone, Default)]
+pub struct ParamEq {
+    /// Document containing the settings
+    document: Arc<serde_json::Value>,
+    /// JSON pointer to the settings within `document`
+    pointer: String,
+    bands: Arc<OnceLock<BTreeMap<String, serde_json::Value>>>,
+}
+
+impl ParamEq {
+    /// Refer to the settings at `pointer` within `document`
+    pub(crate) fn in_document(document: Arc<serde_json::Value>, pointer: String) -> Self {
+        Self {
+            document,
+            pointer,
+            bands: Arc::default(),
+        }
+    }
+
+    /// Get the settings as sent by the speaker, if any
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.document.pointer(&self.pointer).filter(|value| !value.is_null())
+    }
+
+    /// Get the EQ bands by ID, parsing them on the first call
+    ///
+    /// Settings that are not a JSON object yield no bands.
+    pub fn bands(&self) -> &BTreeMap<String, serde_json::Value> {
+        self.bands.get_or_init(|| {
+            self.raw()
+                .and_then(|raw| BTreeMap::deserialize(raw).ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Check if the profile has no EQ bands
+    pub fn is_empty(&self) -> bool {
+        self.raw()
+            .and_then(|raw| raw.as_object())
+            .is_none_or(|bands| bands.is_empty())
+    }
+}
+
+impl std::fmt::Debug for ParamEq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ParamEq").field(&self.raw()).finish()
+    }
+}
+
+impl PartialEq for ParamEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw() == other.raw()
+    }
+}
+
+impl Serialize for ParamEq {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.raw() {
+            Some(raw) => raw.serialize(serializer),
+            None => serializer.serialize_map(Some(0)).and_then(serde::ser::SerializeMap::end),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ParamEq {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        Ok(Self::in_document(Arc::new(raw), String::new()))
+    }
 }
 
 /// Tone control settings
@@ -134,8 +461,84 @@ pub struct ToneSettings {
     pub treble: f64,
 }
 
+impl ToneSettings {
+    /// Lowest setting of each tone control, in dB
+    pub const MIN: f64 = -6.0;
+
+    /// Highest setting of each tone control, in dB
+    pub const MAX: f64 = 6.0;
+
+    /// Limit each setting to the range the speaker accepts
+    pub fn clamped(&self) -> Self {
+        Self {
+            sub: self.sub.clamp(Self::MIN, Self::MAX),
+            mid: self.mid.clamp(Self::MIN, Self::MAX),
+            treble: self.treble.clamp(Self::MIN, Self::MAX),
+        }
+    }
+
+    /// Start a partial tone update; see `Room::adjust_tone`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::ToneSettings;
+    ///
+    /// let current = ToneSettings { sub: 2.0, mid: 0.0, treble: -1.0 };
+    /// let tone = ToneSettings::builder().with_treble(1.0).apply_to(&current);
+    /// assert_eq!((tone.sub, tone.mid, tone.treble), (2.0, 0.0, 1.0));
+    /// ```
+    pub fn builder() -> ToneAdjustment {
+        ToneAdjustment::default()
+    }
+}
+
+/// Partial tone control update
+///
+/// Fields left as `None` keep their current value.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ToneAdjustment {
+    /// Subwoofer gain adjustment
+    pub sub: Option<f64>,
+
+    /// Midrange gain adjustment
+    pub mid: Option<f64>,
+
+    /// Treble gain adjustment
+    pub treble: Option<f64>,
+}
+
+impl ToneAdjustment {
+    /// Set the subwoofer gain adjustment
+    pub fn with_sub(mut self, sub: f64) -> Self {
+        self.sub = Some(sub);
+        self
+    }
+
+    /// Set the midrange gain adjustment
+    pub fn with_mid(mut self, mid: f64) -> Self {
+        self.mid = Some(mid);
+        self
+    }
+
+    /// Set the treble gain adjustment
+    pub fn with_treble(mut self, treble: f64) -> Self {
+        self.treble = Some(treble);
+        self
+    }
+
+    /// Fill in the fields left unset from `current`
+    pub fn apply_to(&self, current: &ToneSettings) -> ToneSettings {
+        ToneSettings {
+            sub: self.sub.unwrap_or(current.sub),
+            mid: self.mid.unwrap_or(current.mid),
+            treble: self.treble.unwrap_or(current.treble),
+        }
+    }
+}
+
 /// Preset configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Preset {
     pub name: String,
     #[serde(default)]
@@ -148,25 +551,170 @@ pub struct Preset {
     /// Whether this is a read-only preset
     #[serde(default)]
     pub readonly: bool,
+
+    /// Fields not known to this crate, kept so the preset serializes unchanged
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Kind of protection mechanism reported by a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtectionKind {
+    /// Output limiter engaged
+    Limiter,
+    /// Thermal protection or temperature warning
+    Thermal,
+    /// Any other protection type reported by the firmware
+    Other(String),
+}
+
+impl ProtectionKind {
+    /// Parse a protection kind from its protocol name
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "limiter" => Self::Limiter,
+            "thermal" => Self::Thermal,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// Limiter engagement, thermal warning, or other protection event from a device
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtectionEvent {
+    /// Device that reported the event
+    pub device_id: DeviceId,
+
+    /// Kind of protection
+    pub kind: ProtectionKind,
+
+    /// Whether the protection is currently engaged
+    pub active: bool,
+
+    /// Affected driver or channel, if reported
+    pub channel: Option<String>,
 }
 
 /// Channel mapping configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChannelMapping {
     /// Mapping from input channels to output gains
     #[serde(flatten)]
     pub channels: BTreeMap<String, ChannelGains>,
 }
 
+impl ChannelMapping {
+    /// Get the gains of a channel
+    pub fn get(&self, channel: &Channel) -> Option<&ChannelGains> {
+        self.channels
+            .iter()
+            .find(|(name, _)| Channel::from(name.as_str()) == *channel)
+            .map(|(_, gains)| gains)
+    }
+
+    /// Iterate over the channels in display order
+    ///
+    /// Left, right, sub, and center come first, followed by any other channels
+    /// by name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dutchdutch_ascend::{Channel, ChannelMapping};
+    /// use serde_json::json;
+    ///
+    /// let mapping: ChannelMapping = serde_json::from_value(json!({
+    ///     "sub": { "left": 0.5, "right": 0.5 },
+    ///     "right": { "left": 0.0, "right": 1.0 },
+    ///     "left": { "left": 1.0, "right": 0.0 },
+    /// }))
+    /// .unwrap();
+    ///
+    /// let order: Vec<Channel> = mapping.iter().map(|(channel, _)| channel).collect();
+    /// assert_eq!(order, [Channel::Left, Channel::Right, Channel::Sub]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Channel, &ChannelGains)> {
+        let mut channels: Vec<_> = self
+            .channels
+            .iter()
+            .map(|(name, gains)| (Channel::from(name.as_str()), gains))
+            .collect();
+        channels.sort_by(|(a, _), (b, _)| a.cmp(b));
+        channels.into_iter()
+    }
+
+    /// Get the channels in display order
+    pub fn channel_names(&self) -> Vec<Channel> {
+        self.iter().map(|(channel, _)| channel).collect()
+    }
+}
+
+/// An input channel of a channel mapping
+///
+/// Orders as listed, with unrecognized channels last by name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Channel {
+    Left,
+    Right,
+    Sub,
+    Center,
+    /// A channel name not recognized by this crate
+    Other(String),
+}
+
+impl From<&str> for Channel {
+    /// Recognize a channel name from the API, ignoring case
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "sub" | "subwoofer" | "lfe" => Self::Sub,
+            "center" | "centre" => Self::Center,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Left => write!(f, "Left"),
+            Self::Right => write!(f, "Right"),
+            Self::Sub => write!(f, "Sub"),
+            Self::Center => write!(f, "Center"),
+            Self::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// An entry of the network state, as reported by the speaker
+///
+/// Rooms and devices are also available parsed, from `AscendClient::rooms` and
+/// `AscendClient::devices`; other entity types are not modeled by this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    /// Key of the entry in the network state
+    pub id: String,
+
+    /// Entity type, e.g. `room` or `device`
+    pub entity_type: String,
+
+    /// The entry's data as sent by the speaker
+    pub data: serde_json::Value,
+}
+
 /// Gains for left and right channels
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChannelGains {
     pub left: f64,
     pub right: f64,
 }
 
 
-/// Discovered room information from cloud discovery
+/// Address and membership of a discovered room
+///
+/// Obtained from a discovered `Room` with `Room::to_discovered`, for storing
+/// or for connecting a separate `AscendClient` to the room's speaker.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveredRoom {
     pub id: RoomId,
@@ -191,8 +739,8 @@ impl DiscoveredRoom {
     ///
     ///     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
     ///
-    ///     if let Some(discovered_room) = discovery.rooms().first() {
-    ///         let client = discovered_room.connect().await?;
+    ///     if let Some(room) = discovery.rooms().first() {
+    ///         let client = room.to_discovered().connect().await?;
     ///         let rooms = client.rooms().await?;
     ///         if let Some(room) = rooms.first() {
     ///             room.set_gain(-20.0).await?;
@@ -208,6 +756,105 @@ impl DiscoveredRoom {
     }
 }
 
+/// Address of a speaker's local API, parsed from a connection string
+///
+/// Accepts `host`, `host:port`, or `ws://host:port`, where the host may be an
+/// IP address (IPv6 in brackets) or a DNS name. The port defaults to
+/// [`SpeakerAddress::DEFAULT_PORT`].
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::SpeakerAddress;
+///
+/// let address: SpeakerAddress = "ws://192.168.1.100:8768".parse().unwrap();
+/// assert_eq!(address.host, "192.168.1.100");
+///
+/// let address: SpeakerAddress = "speakers.local".parse().unwrap();
+/// assert_eq!(address.port, SpeakerAddress::DEFAULT_PORT);
+///
+/// let address: SpeakerAddress = "[fe80::1]:9000".parse().unwrap();
+/// assert_eq!((address.host.as_str(), address.port), ("fe80::1", 9000));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpeakerAddress {
+    pub host: String,
+    pub port: u16,
+}
+
+impl SpeakerAddress {
+    /// Port the speaker's local API listens on by default
+    pub const DEFAULT_PORT: u16 = 8768;
+
+    /// Resolve the host to an IP address
+    pub async fn resolve(&self) -> crate::error::Result<std::net::IpAddr> {
+        tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await?
+            .next()
+            .map(|addr| addr.ip())
+            .ok_or_else(|| {
+                crate::error::AscendError::InvalidArgument(format!(
+                    "Could not resolve host: {}",
+                    self.host
+                ))
+            })
+    }
+}
+
+impl std::str::FromStr for SpeakerAddress {
+    type Err = crate::error::AscendError;
+
+    fn from_str(s: &str) -> crate::error::Result<Self> {
+        let invalid =
+            || crate::error::AscendError::InvalidArgument(format!("Invalid speaker address: {}", s));
+
+        let rest = s.trim();
+        let rest = rest.strip_prefix("ws://").unwrap_or(rest);
+        let rest = rest.strip_suffix('/').unwrap_or(rest);
+        if rest.is_empty() || rest.contains('/') {
+            return Err(invalid());
+        }
+
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            // IPv6 literal, optionally followed by :port
+            let (host, after) = bracketed.split_once(']').ok_or_else(invalid)?;
+            match after {
+                "" => (host, None),
+                _ => (host, Some(after.strip_prefix(':').ok_or_else(invalid)?)),
+            }
+        } else {
+            match rest.rsplit_once(':') {
+                Some((host, _)) if host.contains(':') => return Err(invalid()),
+                Some((host, port)) => (host, Some(port)),
+                None => (rest, None),
+            }
+        };
+
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None => Self::DEFAULT_PORT,
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+impl std::fmt::Display for SpeakerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
 /// Target information from the targets endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TargetsResponse {
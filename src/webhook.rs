@@ -0,0 +1,525 @@
+//! Webhook notifications for speaker events (requires the `webhook` feature)
+//!
+//! A [`WebhookNotifier`] watches a client's state updates and POSTs a JSON
+//! payload to each configured URL when one of its triggers fires, so alerting
+//! and home automation systems can react without speaking the Ascend protocol.
+
+use crate::client::AscendClient;
+use crate::connection::ConnectionStatus;
+use crate::error::{AscendError, Result};
+use crate::subscription::StateUpdate;
+use crate::types::{DeviceId, GainDb, ProtectionKind, RoomId};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Header carrying the HMAC-SHA256 signature of the body, as `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-Ascend-Signature";
+
+/// Header carrying the event name, e.g. `standbyChanged`
+pub const EVENT_HEADER: &str = "X-Ascend-Event";
+
+/// Condition that sends a webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum WebhookTrigger {
+    /// The global gain of a room rose above `threshold` dB
+    VolumeAbove { threshold: GainDb },
+    /// A room entered or left standby
+    StandbyChanged,
+    /// A device reported a limiter, thermal, or other protection event
+    Protection,
+    /// The connection to the speaker was lost
+    SpeakerOffline,
+}
+
+/// A URL and the events posted to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+
+    /// Events that are posted to the URL
+    pub triggers: Vec<WebhookTrigger>,
+
+    /// Key for signing the body; see [`SIGNATURE_HEADER`]
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+impl Webhook {
+    /// Create a webhook posting the given events, unsigned
+    pub fn new(url: impl Into<String>, triggers: impl IntoIterator<Item = WebhookTrigger>) -> Self {
+        Self {
+            url: url.into(),
+            triggers: triggers.into_iter().collect(),
+            secret: None,
+        }
+    }
+
+    /// Sign each body with HMAC-SHA256 using `secret`
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+}
+
+/// Event posted to a webhook
+///
+/// Serialized as the JSON body with an `event` tag and a `timestamp` in Unix
+/// seconds, e.g. `{"event":"standbyChanged","room":"…","standby":true,"timestamp":1718000000}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum WebhookEvent {
+    VolumeAbove {
+        room: RoomId,
+        gain: GainDb,
+        threshold: GainDb,
+    },
+    StandbyChanged {
+        room: RoomId,
+        standby: bool,
+    },
+    Protection {
+        device: DeviceId,
+        /// `limiter`, `thermal`, or the name reported by the firmware
+        kind: String,
+        active: bool,
+        channel: Option<String>,
+    },
+    SpeakerOffline {
+        /// Address of the speaker, as `host:port`
+        speaker: String,
+    },
+}
+
+impl WebhookEvent {
+    /// Event name, as used in the `event` field and [`EVENT_HEADER`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VolumeAbove { .. } => "volumeAbove",
+            Self::StandbyChanged { .. } => "standbyChanged",
+            Self::Protection { .. } => "protection",
+            Self::SpeakerOffline { .. } => "speakerOffline",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+    timestamp: u64,
+}
+
+/// Sends webhooks for the events of one client
+///
+/// Deliveries that fail with a network error, a 5xx status, or 429 are retried
+/// with exponential backoff; other statuses are logged and dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use dutchdutch_ascend::webhook::{Webhook, WebhookNotifier, WebhookTrigger};
+/// use dutchdutch_ascend::{AscendClient, GainDb};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = AscendClient::connect("192.168.1.100", 8768).await?;
+///
+///     let mut notifier = WebhookNotifier::new().with_webhook(
+///         Webhook::new(
+///             "https://alerts.example.com/hooks/speakers",
+///             [
+///                 WebhookTrigger::VolumeAbove { threshold: GainDb::new(-6.0) },
+///                 WebhookTrigger::Protection,
+///                 WebhookTrigger::SpeakerOffline,
+///             ],
+///         )
+///         .with_secret("s3cret"),
+///     );
+///     notifier.start(&client).await?;
+///
+///     tokio::signal::ctrl_c().await?;
+///     notifier.stop();
+///     Ok(())
+/// }
+/// ```
+pub struct WebhookNotifier {
+    webhooks: Vec<Webhook>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    task_handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Default for WebhookNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookNotifier {
+    /// Create a notifier without webhooks that tries each delivery up to 3 times
+    pub fn new() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            task_handle: None,
+        }
+    }
+
+    /// Add a webhook
+    pub fn with_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    /// Set how often a delivery is attempted and the delay before the first retry
+    ///
+    /// The delay doubles after each failed attempt.
+    pub fn with_retries(mut self, max_attempts: u32, initial_backoff: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Start watching the client's events in the background
+    ///
+    /// If the notifier is already running, it will be stopped and restarted.
+    pub async fn start(&mut self, client: &AscendClient) -> Result<()> {
+        self.stop();
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| AscendError::InvalidArgument(format!("Could not create HTTP client: {}", e)))?;
+        let delivery = Arc::new(Delivery {
+            http,
+            webhooks: self.webhooks.clone(),
+            max_attempts: self.max_attempts,
+            initial_backoff: self.initial_backoff,
+        });
+
+        // Seed the last known gains so only crossings trigger `VolumeAbove`
+        let mut gains: HashMap<RoomId, GainDb> = client
+            .rooms()
+            .await?
+            .iter()
+            .map(|room| (room.id(), room.gain().global))
+            .collect();
+        let mut updates = client.subscribe_state().await?;
+        let mut status = client.connection_status();
+        let speaker = client.speaker_address().to_string();
+
+        self.task_handle = Some(tokio::spawn(async move {
+            let mut updates_open = true;
+            let mut previous = *status.borrow_and_update();
+            loop {
+                tokio::select! {
+                    update = updates.recv(), if updates_open => match update {
+                        Ok(update) => delivery.on_update(update, &mut gains),
+                        Err(AscendError::ConnectionClosed) => updates_open = false,
+                        Err(e) => tracing::debug!("Webhook notifier missed updates: {}", e),
+                    },
+                    changed = status.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        // Alert once per drop, not for every status on the way back
+                        let current = *status.borrow_and_update();
+                        if previous == ConnectionStatus::Connected && current != ConnectionStatus::Connected {
+                            delivery.dispatch(WebhookEvent::SpeakerOffline { speaker: speaker.clone() });
+                        }
+                        previous = current;
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stop sending webhooks
+    ///
+    /// Deliveries already in progress are completed.
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.task_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for WebhookNotifier {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Configuration shared by the event loop and delivery tasks
+struct Delivery {
+    http: reqwest::Client,
+    webhooks: Vec<Webhook>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl Delivery {
+    /// Turn a state update into events for the webhooks that want them
+    fn on_update(self: &Arc<Self>, update: StateUpdate, gains: &mut HashMap<RoomId, GainDb>) {
+        match update {
+            StateUpdate::GainChanged { room, gain, .. } => {
+                let previous = gains.insert(room, gain.global);
+                for webhook in &self.webhooks {
+                    for trigger in &webhook.triggers {
+                        let WebhookTrigger::VolumeAbove { threshold } = *trigger else {
+                            continue;
+                        };
+                        if gain.global > threshold && previous.is_some_and(|previous| previous <= threshold) {
+                            let event = WebhookEvent::VolumeAbove {
+                                room,
+                                gain: gain.global,
+                                threshold,
+                            };
+                            self.send(webhook.clone(), event);
+                        }
+                    }
+                }
+            }
+            StateUpdate::StandbyChanged { room, sleeping, .. } => {
+                self.dispatch(WebhookEvent::StandbyChanged { room, standby: sleeping });
+            }
+            StateUpdate::Protection(event) => {
+                let kind = match event.kind {
+                    ProtectionKind::Limiter => "limiter".to_string(),
+                    ProtectionKind::Thermal => "thermal".to_string(),
+                    ProtectionKind::Other(name) => name,
+                };
+                self.dispatch(WebhookEvent::Protection {
+                    device: event.device_id,
+                    kind,
+                    active: event.active,
+                    channel: event.channel,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Send an event to every webhook with a matching trigger
+    ///
+    /// `VolumeAbove` is handled in `on_update`, since each trigger has its own
+    /// threshold.
+    fn dispatch(self: &Arc<Self>, event: WebhookEvent) {
+        for webhook in &self.webhooks {
+            let wanted = webhook.triggers.iter().any(|trigger| {
+                matches!(
+                    (trigger, &event),
+                    (WebhookTrigger::StandbyChanged, WebhookEvent::StandbyChanged { .. })
+                        | (WebhookTrigger::Protection, WebhookEvent::Protection { .. })
+                        | (WebhookTrigger::SpeakerOffline, WebhookEvent::SpeakerOffline { .. })
+                )
+            });
+            if wanted {
+                self.send(webhook.clone(), event.clone());
+            }
+        }
+    }
+
+    /// Deliver an event in the background
+    fn send(self: &Arc<Self>, webhook: Webhook, event: WebhookEvent) {
+        let delivery = self.clone();
+        tokio::spawn(async move { delivery.deliver(&webhook, &event).await });
+    }
+
+    /// Post an event to a webhook, retrying transient failures
+    ///
+    /// Server errors, 429 and transport errors are retried with a doubling
+    /// backoff; other rejections are final.
+    async fn deliver(&self, webhook: &Webhook, event: &WebhookEvent) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let body = match serde_json::to_vec(&Payload { event, timestamp }) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+
+        let mut backoff = self.initial_backoff;
+        for attempt in 1..=self.max_attempts {
+            let mut request = self
+                .http
+                .post(&webhook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event.name())
+                .body(body.clone());
+            if let Some(secret) = &webhook.secret {
+                request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+            }
+
+            let retryable = match request.send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) => {
+                    let status = response.status();
+                    tracing::warn!("Webhook {} rejected {}: {}", webhook.url, event.name(), status);
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    tracing::warn!("Webhook {} failed for {}: {}", webhook.url, event.name(), e);
+                    true
+                }
+            };
+            if !retryable || attempt == self.max_attempts {
+                return;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+}
+
+/// Compute the signature header value for a body
+///
+/// Receivers verify a delivery by computing the same value over the raw body
+/// and comparing it with [`SIGNATURE_HEADER`].
+///
+/// # Example
+///
+/// ```
+/// use dutchdutch_ascend::webhook::sign;
+///
+/// let signature = sign("s3cret", br#"{"event":"protection"}"#);
+/// assert!(signature.starts_with("sha256="));
+/// assert_eq!(signature.len(), "sha256=".len() + 64);
+/// ```
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    let mut signature = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(signature, "{:02x}", byte);
+    }
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::time::Instant;
+
+    /// Request received by the stub server
+    struct Received {
+        at: Instant,
+        head: String,
+        body: Vec<u8>,
+    }
+
+    /// Serve one HTTP response per connection, with the given statuses in order
+    async fn serve(statuses: Vec<u16>) -> (String, tokio::task::JoinHandle<Vec<Received>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut received = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let (head, body) = loop {
+                    let mut chunk = [0; 1024];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&buf[..end]).to_lowercase();
+                    let length: usize = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |value| value.trim().parse().unwrap());
+                    if buf.len() >= end + 4 + length {
+                        break (head, buf[end + 4..end + 4 + length].to_vec());
+                    }
+                };
+                received.push(Received {
+                    at: Instant::now(),
+                    head,
+                    body,
+                });
+                let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            received
+        });
+        (url, handle)
+    }
+
+    fn delivery(max_attempts: u32) -> Delivery {
+        Delivery {
+            http: reqwest::Client::new(),
+            webhooks: Vec::new(),
+            max_attempts,
+            initial_backoff: Duration::from_millis(50),
+        }
+    }
+
+    fn event() -> WebhookEvent {
+        WebhookEvent::SpeakerOffline {
+            speaker: "192.168.1.100:8768".to_string(),
+        }
+    }
+
+    #[test]
+    fn sign_matches_known_vector() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_with_doubling_backoff() {
+        let (url, server) = serve(vec![503, 500, 200]).await;
+        let webhook = Webhook::new(url, vec![WebhookTrigger::SpeakerOffline]).with_secret("s3cret");
+        delivery(5).deliver(&webhook, &event()).await;
+
+        let received = server.await.unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(received[1].at - received[0].at >= Duration::from_millis(50));
+        assert!(received[2].at - received[1].at >= Duration::from_millis(100));
+        for request in &received {
+            let signature = format!("{}: {}", SIGNATURE_HEADER.to_lowercase(), sign("s3cret", &request.body));
+            assert!(request.head.contains(&signature));
+            assert!(request.head.contains("x-ascend-event: speakeroffline"));
+        }
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (url, server) = serve(vec![400]).await;
+        let webhook = Webhook::new(url, vec![WebhookTrigger::SpeakerOffline]);
+        delivery(5).deliver(&webhook, &event()).await;
+
+        let received = server.await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(!received[0].head.contains(&SIGNATURE_HEADER.to_lowercase()));
+    }
+
+    #[tokio::test]
+    async fn delivery_gives_up_after_max_attempts() {
+        let (url, server) = serve(vec![503, 429, 200]).await;
+        let webhook = Webhook::new(url, vec![WebhookTrigger::SpeakerOffline]);
+        delivery(2).deliver(&webhook, &event()).await;
+
+        // The server is still waiting for the third request
+        assert!(tokio::time::timeout(Duration::from_millis(300), server).await.is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! Replay of recorded sessions
+#![cfg(feature = "recorder")]
+
+use dutchdutch_ascend::{AscendClient, ConnectOptions, StateUpdate};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const SUBSCRIPTION_ID: &str = "0b5e2f4a-7c1d-4e8f-9a3b-6d2c1e0f4a5b";
+const ROOM_ID: &str = "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d";
+
+fn line(direction: &str, message: Value) -> String {
+    json!({ "timestampMs": 0, "direction": direction, "message": message }).to_string()
+}
+
+/// Notification of a room document, as recorded under the subscription's ID
+fn notify(room: Value) -> Value {
+    json!({
+        "meta": { "id": SUBSCRIPTION_ID, "endpoint": "network", "method": "notify", "type": "network" },
+        "data": { "state": { "room": { "data": room } } },
+    })
+}
+
+#[tokio::test]
+async fn every_notification_of_a_subscription_is_replayed() {
+    let recording = [
+        line("sent", json!({ "meta": { "id": SUBSCRIPTION_ID, "endpoint": "network", "method": "subscribe" } })),
+        line("received", json!({ "meta": { "id": SUBSCRIPTION_ID, "endpoint": "network", "method": "subscribe" } })),
+        line("received", notify(json!({ "type": "room", "id": ROOM_ID, "gain": { "global": -25.0 } }))),
+        line("received", notify(json!({ "type": "room", "id": ROOM_ID, "gain": { "global": -20.0 } }))),
+    ];
+    let path = std::env::temp_dir().join(format!("ascend-replay-{}.jsonl", std::process::id()));
+    std::fs::write(&path, recording.join("\n")).unwrap();
+
+    let client = AscendClient::replay(&path, ConnectOptions::default()).await.unwrap();
+    let mut updates = client.subscribe_state().await.unwrap();
+    let mut gains = Vec::new();
+    while gains.len() < 2 {
+        let Ok(update) = tokio::time::timeout(Duration::from_secs(1), updates.recv()).await else {
+            panic!("only {:?} were replayed", gains);
+        };
+        if let StateUpdate::RoomUpdate(document) = update.unwrap() {
+            gains.push(document["gain"]["global"].clone());
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(gains, [json!(-25.0), json!(-20.0)]);
+}
@@ -0,0 +1,334 @@
+//! Room state of a client against an in-memory speaker
+
+use dutchdutch_ascend::{
+    AscendClient, ConnectOptions, GainDb, Result, Room, RoomGroup, RoomScene, Scene, SceneRunner, StateUpdate,
+    ToneSettings, Transport,
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const ROOM_ID: &str = "5f0c7a52-9a63-4c5e-8d1b-2f3e4a5b6c7d";
+
+/// Speaker that acknowledges every command without acting on it
+///
+/// Its room document only changes when the test changes it.
+struct FakeSpeaker {
+    room: Arc<Mutex<Value>>,
+    devices: Arc<Mutex<Vec<Value>>>,
+    tx: mpsc::UnboundedSender<String>,
+    rx: mpsc::UnboundedReceiver<String>,
+}
+
+impl Transport for FakeSpeaker {
+    async fn send(&mut self, text: String) -> Result<()> {
+        let request: Value = serde_json::from_str(&text)?;
+        let meta = &request["meta"];
+        let data = match (meta["endpoint"].as_str(), meta["method"].as_str()) {
+            (Some("network"), Some("read")) => {
+                let mut network = network(&self.room.lock().unwrap());
+                for device in self.devices.lock().unwrap().iter() {
+                    network["state"][device["id"].as_str().unwrap()] = json!({ "data": device });
+                }
+                network
+            }
+            _ => Value::Null,
+        };
+        let reply = json!({
+            "meta": { "id": meta["id"], "endpoint": meta["endpoint"], "method": meta["method"] },
+            "data": data,
+        });
+        let _ = self.tx.send(reply.to_string());
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Option<Result<String>> {
+        self.rx.recv().await.map(Ok)
+    }
+}
+
+/// Test side of a `FakeSpeaker`
+struct Speaker {
+    room: Arc<Mutex<Value>>,
+    devices: Arc<Mutex<Vec<Value>>>,
+    tx: mpsc::UnboundedSender<String>,
+}
+
+impl Speaker {
+    /// Change a field of the speaker's room document
+    fn set(&self, key: &str, value: Value) {
+        self.room.lock().unwrap()[key] = value;
+    }
+
+    /// Add a device entry to the speaker's network state
+    fn add_device(&self, id: &str, tags: &[&str]) {
+        let device = json!({ "type": "device", "id": id, "name": id, "tags": tags });
+        self.devices.lock().unwrap().push(device);
+    }
+
+    /// Send a network notification carrying a room document
+    fn notify(&self, room: Value) {
+        let notification = json!({
+            "meta": {
+                "id": "00000000-0000-4000-8000-000000000000",
+                "endpoint": "network",
+                "method": "notify",
+                "type": "network",
+            },
+            "data": network(&room),
+        });
+        let _ = self.tx.send(notification.to_string());
+    }
+
+    /// Send the full room document as a notification
+    fn notify_room(&self) {
+        let room = self.room.lock().unwrap().clone();
+        self.notify(room);
+    }
+}
+
+fn network(room: &Value) -> Value {
+    json!({ "state": { "room": { "data": room } } })
+}
+
+async fn connect() -> (AscendClient, Room, Speaker) {
+    let room = Arc::new(Mutex::new(json!({
+        "type": "room",
+        "id": ROOM_ID,
+        "name": "Studio",
+        "gain": { "global": -30.0 },
+        "mute": { "global": false },
+        "selectedInput": "USB",
+        "inputModes": ["USB", "XLR"],
+    })));
+    let devices = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = mpsc::unbounded_channel();
+    let fake = FakeSpeaker {
+        room: room.clone(),
+        devices: devices.clone(),
+        tx: tx.clone(),
+        rx,
+    };
+    let client = AscendClient::with_transport(fake, ConnectOptions::default()).await;
+    let handle = client.room().await.unwrap();
+    (client, handle, Speaker { room, devices, tx })
+}
+
+/// Wait for the room state to change, e.g. after a notification
+async fn changed(room: &Room, mut watch: tokio::sync::watch::Receiver<dutchdutch_ascend::RoomState>) {
+    tokio::time::timeout(Duration::from_secs(1), watch.changed())
+        .await
+        .unwrap_or_else(|_| panic!("{} did not change", room.name()))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn notification_corrects_optimistic_gain() {
+    let (_client, room, speaker) = connect().await;
+
+    room.set_gain(-10.0).await.unwrap();
+    assert_eq!(room.gain().global, GainDb::new(-10.0));
+
+    // The speaker didn't take the change and reports its unchanged document
+    let watch = room.watch();
+    speaker.notify_room();
+    changed(&room, watch).await;
+    assert_eq!(room.gain().global, GainDb::new(-30.0));
+}
+
+#[tokio::test]
+async fn refresh_corrects_optimistic_gain() {
+    let (client, room, _speaker) = connect().await;
+
+    room.set_gain(-10.0).await.unwrap();
+    room.refresh().await.unwrap();
+    assert_eq!(room.gain().global, GainDb::new(-30.0));
+
+    room.set_gain(-10.0).await.unwrap();
+    client.refresh_all().await.unwrap();
+    assert_eq!(room.gain().global, GainDb::new(-30.0));
+}
+
+#[tokio::test]
+async fn partial_notification_keeps_optimistic_input() {
+    let (_client, room, speaker) = connect().await;
+
+    room.set_input("XLR").await.unwrap();
+    speaker.set("selectedInput", json!("XLR"));
+
+    // A gain-only document says nothing about the input
+    let watch = room.watch();
+    speaker.notify(json!({ "type": "room", "id": ROOM_ID, "gain": { "global": -25.0 } }));
+    changed(&room, watch).await;
+    assert_eq!(room.gain().global, GainDb::new(-25.0));
+    assert_eq!(room.selected_input().as_deref(), Some("XLR"));
+}
+
+#[tokio::test]
+async fn confirmed_setter_returns_once_reported() {
+    let (_client, room, speaker) = connect().await;
+
+    let notify = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        speaker.set("gain", json!({ "global": -10.0 }));
+        speaker.notify_room();
+    };
+    let (result, ()) = tokio::join!(room.set_gain_confirmed(-10.0), notify);
+    result.unwrap();
+}
+
+#[tokio::test]
+async fn confirmed_setter_ignores_optimistic_value() {
+    let (_client, room, speaker) = connect().await;
+
+    // The speaker reports an unrelated change, then its unchanged gain
+    let notify = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        speaker.notify(json!({ "type": "room", "id": ROOM_ID, "sleep": false }));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        speaker.notify_room();
+    };
+    let (result, ()) = tokio::join!(room.set_gain_confirmed(-10.0), notify);
+    assert!(matches!(result, Err(dutchdutch_ascend::AscendError::Timeout { .. })), "{:?}", result);
+}
+
+#[tokio::test]
+async fn partial_tone_updates_build_on_each_other() {
+    let (_client, room, speaker) = connect().await;
+    speaker.set("selectedVoicingProfile", json!("neutral"));
+    speaker.set("voicing", json!({ "neutral": { "name": "Neutral", "sub": 0.0, "bass": 0.0, "treble": 0.0 } }));
+    room.refresh().await.unwrap();
+
+    room.adjust_tone(ToneSettings::builder().with_sub(2.0)).await.unwrap();
+    room.adjust_tone(ToneSettings::builder().with_treble(1.0)).await.unwrap();
+    let tone = room.current_tone().unwrap();
+    assert_eq!((tone.sub, tone.mid, tone.treble), (2.0, 0.0, 1.0));
+}
+
+#[tokio::test]
+async fn coalesced_partial_documents_are_overlaid() {
+    let (client, _room, speaker) = connect().await;
+    let mut updates = client.subscribe_state().await.unwrap().coalesce(Duration::from_millis(100));
+
+    speaker.notify(json!({ "type": "room", "id": ROOM_ID, "gain": { "global": -25.0 } }));
+    speaker.notify(json!({ "type": "room", "id": ROOM_ID, "mute": { "global": true } }));
+    let StateUpdate::RoomUpdate(document) = updates.recv().await.unwrap() else {
+        panic!("expected the room document first");
+    };
+    assert_eq!(document["gain"]["global"], json!(-25.0));
+    assert_eq!(document["mute"]["global"], json!(true));
+}
+
+#[tokio::test]
+async fn capabilities_follow_member_tags_and_room_fields() {
+    let (_client, room, speaker) = connect().await;
+    let capabilities = room.capabilities().await.unwrap();
+    assert!(!capabilities.has_sub_output);
+    assert!(!capabilities.has_analog_input);
+    assert!(!capabilities.supports_streaming);
+
+    speaker.add_device("left", &["8c"]);
+    speaker.add_device("sub", &["subwoofer"]);
+    speaker.set("members", json!({ "left": "left", "sub": "sub" }));
+    speaker.set("inputModes", json!(["USB", "aes", "analogLowGain"]));
+    speaker.set("streaming", json!(false));
+    room.refresh().await.unwrap();
+
+    let capabilities = room.capabilities().await.unwrap();
+    assert!(capabilities.has_sub_output);
+    assert!(capabilities.has_analog_input);
+    assert!(capabilities.supports_streaming);
+}
+
+#[tokio::test]
+async fn batch_reports_each_change() {
+    let (_client, room, _speaker) = connect().await;
+    room.set_safety_limit(-20.0);
+
+    let results = room.batch().input("XLR").gain(-10.0).mute(true).send().await;
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(dutchdutch_ascend::AscendError::SafetyLimit { .. })));
+    assert!(results[2].is_ok());
+    assert_eq!(room.selected_input().as_deref(), Some("XLR"));
+    assert_eq!(room.gain().global, GainDb::new(-30.0));
+    assert!(room.mute().global);
+}
+
+#[tokio::test]
+async fn preset_above_safety_limit_is_lowered() {
+    let (_client, room, speaker) = connect().await;
+    room.set_safety_limit(-20.0);
+
+    // The speaker applies the preset's own gain
+    speaker.set("gain", json!({ "global": -10.0 }));
+    room.select_preset("loud").await.unwrap();
+    assert_eq!(room.gain().global, GainDb::new(-20.0));
+}
+
+#[tokio::test]
+async fn concurrent_tone_steps_build_on_each_other() {
+    let (_client, room, speaker) = connect().await;
+    speaker.set("selectedVoicingProfile", json!("neutral"));
+    speaker.set("voicing", json!({ "neutral": { "name": "Neutral", "sub": 0.0, "bass": 0.0, "treble": 0.0 } }));
+    room.refresh().await.unwrap();
+
+    let (first, second, third) = tokio::join!(room.adjust_bass(0.5), room.adjust_bass(0.5), room.adjust_bass(0.5));
+    first.unwrap();
+    second.unwrap();
+    third.unwrap();
+    assert_eq!(room.current_tone().unwrap().mid, 1.5);
+}
+
+#[tokio::test]
+async fn unchanged_notification_does_not_wake_watchers() {
+    let (_client, room, speaker) = connect().await;
+
+    let watch = room.watch();
+    speaker.notify_room();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!watch.has_changed().unwrap());
+    assert!(room.history().is_empty());
+}
+
+#[tokio::test]
+async fn group_keeps_its_gain_while_a_room_is_clamped() {
+    let (_client, room, speaker) = connect().await;
+    speaker.set("gain", json!({ "global": -30.0, "limits": { "min": -80.0, "max": 0.0, "step": 0.5 } }));
+    room.refresh().await.unwrap();
+    room.set_safety_limit(-20.0);
+    let group = RoomGroup::new([room.clone()]);
+
+    let member = group.set_gain(-10.0).await.remove(&room.id()).unwrap().unwrap();
+    assert_eq!(member.gain, GainDb::new(-20.0));
+    assert!(member.clamped);
+    assert_eq!(group.gain(), Some(GainDb::new(-10.0)));
+
+    // Moving down from the group gain, not the clamped room gain
+    let member = group.adjust_gain(-15.0).await.remove(&room.id()).unwrap().unwrap();
+    assert_eq!(member.gain, GainDb::new(-25.0));
+    assert!(!member.clamped);
+}
+
+#[tokio::test]
+async fn failed_scene_restores_previous_settings() {
+    let (_client, room, _speaker) = connect().await;
+    room.set_safety_limit(-20.0);
+
+    // The input and mute are sent, then the gain is refused
+    let scene = Scene::new("Loud").with_room(
+        room.id(),
+        RoomScene {
+            input: Some("XLR".to_string()),
+            gain: Some(GainDb::new(-10.0)),
+            mute: Some(true),
+            ..Default::default()
+        },
+    );
+    let result = SceneRunner::new([room.clone()]).apply(&scene).await;
+    assert!(matches!(result, Err(dutchdutch_ascend::AscendError::SafetyLimit { .. })));
+    assert_eq!(room.selected_input().as_deref(), Some("USB"));
+    assert_eq!(room.gain().global, GainDb::new(-30.0));
+    assert!(!room.mute().global);
+}
@@ -0,0 +1,134 @@
+//! Value types shared across the API
+
+use dutchdutch_ascend::{AscendClient, AscendError, GainDb, GainLimits, SpeakerAddress};
+
+fn limits() -> GainLimits {
+    GainLimits {
+        min: -80.0,
+        max: 0.0,
+        step: 0.5,
+    }
+}
+
+#[test]
+fn gain_arithmetic_works_in_db() {
+    let mut gain = GainDb::new(-20.0) + GainDb::new(2.5);
+    assert_eq!(gain, GainDb::new(-17.5));
+    assert_eq!(gain - GainDb::new(2.5), GainDb::new(-20.0));
+
+    gain += GainDb::new(7.5);
+    assert_eq!(gain, GainDb::new(-10.0));
+    gain -= GainDb::new(5.0);
+    assert_eq!(gain, GainDb::new(-15.0));
+
+    assert_eq!(-gain, GainDb::new(15.0));
+    assert_eq!(gain.abs(), GainDb::new(15.0));
+    assert_eq!(gain.min(GainDb::new(-30.0)), GainDb::new(-30.0));
+    assert_eq!(gain.max(GainDb::new(-30.0)), gain);
+    assert!(GainDb::new(-10.0) > GainDb::new(-20.0));
+    assert_eq!(f64::from(gain), -15.0);
+}
+
+#[test]
+fn clamp_to_limits_the_gain_to_the_range() {
+    assert_eq!(GainDb::new(3.0).clamp_to(&limits()), GainDb::new(0.0));
+    assert_eq!(GainDb::new(-95.0).clamp_to(&limits()), GainDb::new(-80.0));
+    assert_eq!(GainDb::new(-20.3).clamp_to(&limits()), GainDb::new(-20.3));
+}
+
+#[test]
+fn clamp_to_does_not_panic_on_inverted_limits() {
+    let inverted = GainLimits {
+        min: -10.0,
+        max: -20.0,
+        step: 0.5,
+    };
+    // The minimum wins, as it is applied last
+    assert_eq!(GainDb::new(0.0).clamp_to(&inverted), GainDb::new(-10.0));
+    assert_eq!(GainDb::new(-30.0).clamp_to(&inverted), GainDb::new(-10.0));
+}
+
+#[test]
+fn snap_to_rounds_to_steps_from_the_minimum() {
+    assert_eq!(GainDb::new(-20.3).snap_to(&limits()), GainDb::new(-20.5));
+    assert_eq!(GainDb::new(-20.2).snap_to(&limits()), GainDb::new(-20.0));
+    assert_eq!(GainDb::new(4.0).snap_to(&limits()), GainDb::new(0.0));
+
+    let offset = GainLimits {
+        min: -79.8,
+        max: 0.0,
+        step: 1.0,
+    };
+    assert!((GainDb::new(-20.0).snap_to(&offset).db() - -19.8).abs() < 1e-9);
+
+    let stepless = GainLimits { step: 0.0, ..limits() };
+    assert_eq!(GainDb::new(-20.3).snap_to(&stepless), GainDb::new(-20.3));
+    assert_eq!(GainDb::new(1.0).snap_to(&stepless), GainDb::new(0.0));
+}
+
+#[test]
+fn gain_parses_with_or_without_a_db_suffix() {
+    assert_eq!("-20".parse::<GainDb>().unwrap(), GainDb::new(-20.0));
+    assert_eq!(" -20.5 dB ".parse::<GainDb>().unwrap(), GainDb::new(-20.5));
+    assert_eq!("3db".parse::<GainDb>().unwrap(), GainDb::new(3.0));
+    assert!("loud".parse::<GainDb>().is_err());
+    assert!("dB".parse::<GainDb>().is_err());
+    assert_eq!(GainDb::new(-20.5).to_string(), "-20.5");
+}
+
+fn address(s: &str) -> (String, u16) {
+    let address: SpeakerAddress = s.parse().unwrap();
+    (address.host, address.port)
+}
+
+#[test]
+fn speaker_address_accepts_hosts_with_and_without_port() {
+    assert_eq!(address("192.168.1.100"), ("192.168.1.100".to_string(), 8768));
+    assert_eq!(address("speakers.local:9000"), ("speakers.local".to_string(), 9000));
+    assert_eq!(address(" ws://192.168.1.100:8768/ "), ("192.168.1.100".to_string(), 8768));
+}
+
+#[test]
+fn speaker_address_accepts_bracketed_ipv6() {
+    assert_eq!(address("[fe80::1]:9000"), ("fe80::1".to_string(), 9000));
+    assert_eq!(address("ws://[::1]"), ("::1".to_string(), 8768));
+
+    // Display brackets the host again, so the address parses back to itself
+    let address: SpeakerAddress = "[fe80::1]:9000".parse().unwrap();
+    assert_eq!(address.to_string(), "[fe80::1]:9000");
+    assert_eq!(address.to_string().parse::<SpeakerAddress>().unwrap(), address);
+}
+
+#[test]
+fn speaker_address_rejects_malformed_input() {
+    for input in [
+        "",
+        "ws://",
+        "host:",
+        "host:abc",
+        "host:99999",
+        ":8768",
+        "fe80::1",
+        "[fe80::1",
+        "[fe80::1]9000",
+        "http://host",
+        "wss://host",
+        "host:8768/api",
+    ] {
+        match input.parse::<SpeakerAddress>() {
+            Err(AscendError::InvalidArgument(_)) => {}
+            other => panic!("{:?} parsed as {:?}", input, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn connect_url_rejects_bad_addresses_before_connecting() {
+    for url in ["host:99999", "fe80::1", "https://host"] {
+        match AscendClient::connect_url(url).await {
+            Err(AscendError::InvalidArgument(message)) => assert!(message.contains(url)),
+            Err(e) => panic!("{} failed with {}", url, e),
+            Ok(_) => panic!("{} connected", url),
+        }
+    }
+}